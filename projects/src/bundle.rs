@@ -0,0 +1,244 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use todotxt::parser::is_name;
+
+use crate::{Error, Location, Metadata, Part, Projects, Storage, projects::parse_todos};
+
+const MANIFEST_FILE: &str = "manifest.toml";
+const VERSION: u32 = 1;
+
+/// What [`Projects::import`] does with projects already in the workspace.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Add the todos of projects that exist on both sides, keeping the
+    /// workspace's README and metadata unless they are empty.
+    #[default]
+    Merge,
+    /// Delete every project first. Directory workspaces keep them in the trash.
+    /// Refused for a shared `todo.txt`, where it would drop lines of other lists.
+    Replace,
+}
+
+/// `manifest.toml` at the root of a bundle.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    created: DateTime<Utc>,
+    projects: Vec<Location>,
+}
+
+impl<S: Storage + ?Sized> Projects<S> {
+    /// Write every project to a tar archive laid out like a projects directory,
+    /// with a manifest listing them. Returns the number of projects.
+    ///
+    /// Reads what is in the storage, so changes not yet [synced](Self::sync) are left out.
+    pub fn export(&self, writer: impl Write) -> Result<usize, Error> {
+        let mut builder = tar::Builder::new(writer);
+        let now = Utc::now();
+
+        let manifest = Manifest {
+            version: VERSION,
+            created: now,
+            projects: self.iter().map(|m| m.location()).collect(),
+        };
+        let manifest_toml = toml::to_string(&manifest).expect("manifest serializes to toml");
+        append(&mut builder, Path::new(MANIFEST_FILE), &manifest_toml, now)?;
+
+        for location in &manifest.projects {
+            for part in Part::ALL {
+                let content = self.storage().read(location, part)?;
+                if !content.is_empty() {
+                    append(
+                        &mut builder,
+                        &location.path().join(part.file_name()),
+                        &content,
+                        now,
+                    )?;
+                }
+            }
+        }
+
+        builder.into_inner()?.flush()?;
+
+        Ok(manifest.projects.len())
+    }
+
+    /// Read projects written by [`export`](Self::export). Returns the number of projects.
+    ///
+    /// Parts the storage can't keep, like READMEs in a plain `todo.txt`, are left out.
+    pub fn import(&mut self, reader: impl Read, mode: ImportMode) -> Result<usize, Error> {
+        let mut manifest = None;
+        let mut files = BTreeMap::<PathBuf, String>::new();
+
+        for entry in tar::Archive::new(reader).entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?.into_owned();
+            let mut content = String::default();
+            entry.read_to_string(&mut content)?;
+
+            if path == Path::new(MANIFEST_FILE) {
+                manifest = Some(
+                    toml::from_str::<Manifest>(&content)
+                        .map_err(|err| Error::InvalidBundle(err.message().to_string()))?,
+                );
+            } else if path.components().all(|m| matches!(m, Component::Normal(_))) {
+                files.insert(path, content);
+            }
+        }
+
+        let manifest = manifest.ok_or_else(|| Error::InvalidBundle("no manifest".to_string()))?;
+        if manifest.version != VERSION {
+            return Err(Error::InvalidBundle(format!(
+                "unsupported version {}",
+                manifest.version
+            )));
+        }
+        if let Some(location) = manifest.projects.iter().find(|m| !is_name(&m.name)) {
+            return Err(Error::InvalidName(location.name.clone()));
+        }
+
+        if mode == ImportMode::Replace {
+            if self.storage().is_shared() {
+                return Err(Error::Unsupported(
+                    "replacing the projects of a shared file",
+                ));
+            }
+            let names = self
+                .iter()
+                .map(|m| m.name().to_string())
+                .collect::<Vec<_>>();
+            for name in names {
+                self.delete(&name)?;
+            }
+        }
+
+        let mut paths = Vec::default();
+
+        for location in &manifest.projects {
+            let read = |part: Part| {
+                files
+                    .get(&location.path().join(part.file_name()))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+
+            let Some(target) = self.find(&location.name).map(|m| m.location()) else {
+                // Written as is, so nothing gets lost on the way
+                let _lock = self.storage().lock(location)?;
+                for part in Part::ALL {
+                    let content = read(part);
+                    if content.is_empty() {
+                        continue;
+                    }
+                    match self.storage().write(location, part, &content) {
+                        Err(Error::Unsupported(_)) if part != Part::Todos => {}
+                        result => result?,
+                    }
+                }
+                self.open_project(location.clone())?;
+                paths.push(location.path());
+                continue;
+            };
+
+            // Completed todos aren't loaded, so they are merged in the storage
+            if merge_done(self.storage(), &target, &read(Part::Done))? {
+                paths.push(target.path());
+            }
+            let project = self.find_mut(&location.name).unwrap();
+
+            let source = PathBuf::from(&location.name);
+            let todos = parse_todos(&source.join(Part::Todos.file_name()), &read(Part::Todos))?;
+            for todo in todos.iter() {
+                if !project.todos()?.iter().any(|m| m == todo) {
+                    project.todos_mut()?.create_todo(todo.clone());
+                }
+            }
+
+            if project.description()?.trim().is_empty() {
+                let description = read(Part::Description);
+                if !description.trim().is_empty() {
+                    *project.description_mut()? = description;
+                }
+            }
+
+            if *project.metadata()? == Metadata::default() {
+                let metadata = Metadata::parse(
+                    &source.join(Part::Metadata.file_name()),
+                    &read(Part::Metadata),
+                )?;
+                if metadata != Metadata::default() {
+                    *project.metadata_mut()? = metadata;
+                }
+            }
+        }
+
+        self.sync_moved(
+            paths,
+            &format!("import {} projects", manifest.projects.len()),
+        )?;
+
+        Ok(manifest.projects.len())
+    }
+}
+
+/// Add the lines of `done` missing from the project's `done.txt`.
+/// Returns whether anything was added.
+fn merge_done<S: Storage + ?Sized>(
+    storage: &S,
+    location: &Location,
+    done: &str,
+) -> Result<bool, Error> {
+    if done.is_empty() {
+        return Ok(false);
+    }
+
+    let _lock = storage.lock(location)?;
+    let mut merged = storage.read(location, Part::Done)?;
+    let missing = done
+        .lines()
+        .filter(|line| !merged.lines().any(|m| m == *line))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    for line in missing {
+        merged.push_str(&line);
+        merged.push('\n');
+    }
+
+    match storage.write(location, Part::Done, &merged) {
+        Ok(()) => Ok(true),
+        Err(Error::Unsupported(_)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn append(
+    builder: &mut tar::Builder<impl Write>,
+    path: &Path,
+    content: &str,
+    modified: DateTime<Utc>,
+) -> Result<(), Error> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(modified.timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, path, content.as_bytes())?;
+    Ok(())
+}
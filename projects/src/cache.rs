@@ -0,0 +1,58 @@
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, fs};
+
+const CACHE_FILE: &str = ".cache.toml";
+
+/// Todo counts of a project, cached between runs so listing projects
+/// doesn't parse every `todo.txt`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub open: usize,
+    pub done: usize,
+    /// When the todos were last changed on disk.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Cache {
+    projects: BTreeMap<String, Summary>,
+}
+
+impl Cache {
+    /// A missing or unreadable cache is just empty.
+    pub fn load(dir: &Path) -> Cache {
+        std::fs::read_to_string(dir.join(CACHE_FILE))
+            .ok()
+            .and_then(|m| toml::from_str(&m).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), Error> {
+        let content = toml::to_string(self).expect("cache serializes to toml");
+        fs::write_atomic(&dir.join(CACHE_FILE), content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Summary> {
+        self.projects.get(name).copied()
+    }
+
+    /// Returns true if the entry changed.
+    pub fn set(&mut self, name: &str, summary: Summary) -> bool {
+        self.projects.insert(name.to_string(), summary) != Some(summary)
+    }
+
+    /// Drop entries for projects not in `names`. Returns true if any were dropped.
+    pub fn retain<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) -> bool {
+        let names = names.into_iter().collect::<Vec<_>>();
+        let len = self.projects.len();
+        self.projects
+            .retain(|name, _| names.contains(&name.as_str()));
+        self.projects.len() != len
+    }
+}
@@ -0,0 +1,205 @@
+//! Keeps `- [ ]` checklists in a project's README in step with its todos.
+//!
+//! Every checklist item is linked to a todo through an `id:` key on the todo and
+//! a `<!-- todo:ID -->` marker at the end of the item, so renaming either side
+//! doesn't create duplicates. The marker reads `<!-- todo:ID x -->` when the item
+//! was checked at the last sync, so a sync only applies the side that changed since.
+use todotxt::{Clock, Todo, Value};
+
+use crate::{Error, Project, Projects, Storage, harvest};
+
+pub const ID_KEY: &str = "id";
+
+const MARKER_START: &str = "<!-- todo:";
+const MARKER_END: &str = "-->";
+
+/// Outcome of [`Project::sync_checklist`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Checklist {
+    /// Items turned into todos.
+    pub added: usize,
+    /// Items checked because their todo was done.
+    pub checked: usize,
+    /// Items unchecked because their todo was reopened.
+    pub unchecked: usize,
+    /// Todos completed because their item was checked.
+    pub completed: usize,
+    /// Todos reopened because their item was unchecked.
+    pub reopened: usize,
+}
+
+struct Item<'a> {
+    /// Indentation and bullet, kept as written.
+    prefix: &'a str,
+    checked: bool,
+    text: &'a str,
+    id: Option<i64>,
+    /// Whether the item was checked at the last sync.
+    synced: bool,
+}
+
+impl Item<'_> {
+    fn render(&self, checked: bool, id: i64) -> String {
+        let (mark, synced) = if checked { ('x', " x") } else { (' ', "") };
+        format!(
+            "{}[{mark}] {} {MARKER_START}{id}{synced} {MARKER_END}",
+            self.prefix, self.text
+        )
+    }
+}
+
+fn parse_item(line: &str) -> Option<Item<'_>> {
+    let rest = line.trim_start();
+    let bullet = rest.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    let prefix = &line[..line.len() - bullet.len()];
+
+    let checked = match bullet.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = bullet[3..].strip_prefix(' ')?.trim_end();
+
+    let (text, id, synced) = match text.rfind(MARKER_START) {
+        Some(idx) if text.ends_with(MARKER_END) => {
+            let mut marker =
+                text[idx + MARKER_START.len()..text.len() - MARKER_END.len()].split_whitespace();
+            let id = marker.next()?.parse().ok()?;
+            let synced = match marker.next() {
+                Some("x") => true,
+                None => false,
+                Some(_) => return None,
+            };
+            (text[..idx].trim_end(), Some(id), synced)
+        }
+        _ => (text, None, checked),
+    };
+
+    (!text.is_empty()).then_some(Item {
+        prefix,
+        checked,
+        text,
+        id,
+        synced,
+    })
+}
+
+fn id(todo: &Todo) -> Option<i64> {
+    match todo.get(ID_KEY)? {
+        Value::Int(id) => Some(*id),
+        _ => None,
+    }
+}
+
+impl<S: Storage + ?Sized> Project<S> {
+    /// Turn new checklist items in the README into todos and carry checking or
+    /// unchecking an item over to its todo, and completing or reopening a todo
+    /// over to its item. When both changed since the last sync they agree.
+    ///
+    /// Items whose todo was removed are left alone.
+    pub fn sync_checklist(&mut self, clock: &impl Clock) -> Result<Checklist, Error> {
+        let mut checklist = Checklist::default();
+        let mut next_id = self.todos()?.iter().filter_map(id).max().unwrap_or(0) + 1;
+
+        let description = self.description()?.to_string();
+        let mut lines = Vec::default();
+        let mut added = Vec::default();
+        let mut changed = false;
+
+        for line in description.lines() {
+            let Some(item) = parse_item(line) else {
+                lines.push(line.to_string());
+                continue;
+            };
+
+            let Some(item_id) = item.id else {
+                let mut todo = harvest::new_todo(item.text, self.name());
+                todo.created = Some(clock.today());
+                todo.values
+                    .insert(ID_KEY.to_string(), vec![Value::Int(next_id)]);
+                if item.checked {
+                    todo.complete(clock);
+                }
+
+                lines.push(item.render(item.checked, next_id));
+                added.push(todo);
+                next_id += 1;
+                continue;
+            };
+
+            let Some(idx) = self.todos()?.iter().position(|m| id(m) == Some(item_id)) else {
+                lines.push(line.to_string());
+                continue;
+            };
+
+            let done = self.todos()?.get(idx).unwrap().done;
+            let state = if item.checked != item.synced {
+                item.checked
+            } else {
+                done
+            };
+
+            match (done, state) {
+                (false, true) => {
+                    self.todos_mut()?.get_mut(idx).unwrap().complete(clock);
+                    checklist.completed += 1;
+                }
+                (true, false) => {
+                    self.todos_mut()?.get_mut(idx).unwrap().reopen();
+                    checklist.reopened += 1;
+                }
+                _ => {}
+            }
+
+            match (item.checked, state) {
+                (false, true) => checklist.checked += 1,
+                (true, false) => checklist.unchecked += 1,
+                _ => {}
+            }
+
+            if item.checked == state && item.synced == state {
+                lines.push(line.to_string());
+            } else {
+                lines.push(item.render(state, item_id));
+                changed = true;
+            }
+        }
+
+        checklist.added = added.len();
+        if checklist.added > 0 || changed {
+            let mut out = lines.join("\n");
+            if description.ends_with('\n') {
+                out.push('\n');
+            }
+            *self.description_mut()? = out;
+        }
+
+        for todo in added {
+            self.todos_mut()?.create_todo(todo);
+        }
+
+        Ok(checklist)
+    }
+}
+
+impl<S: Storage + ?Sized> Projects<S> {
+    /// Sync the checklists of every project with `checklist = true` in its metadata.
+    pub fn sync_checklists(&mut self, clock: &impl Clock) -> Result<Checklist, Error> {
+        let mut total = Checklist::default();
+
+        for project in self.iter_mut() {
+            if !project.metadata()?.checklist {
+                continue;
+            }
+
+            let checklist = project.sync_checklist(clock)?;
+            total.added += checklist.added;
+            total.checked += checklist.checked;
+            total.unchecked += checklist.unchecked;
+            total.completed += checklist.completed;
+            total.reopened += checklist.reopened;
+        }
+
+        Ok(total)
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use todotxt::parser::is_name;
+
+use std::rc::Rc;
+
+use crate::{DirStorage, Error, FileStorage, Projects, Storage};
+
+/// Overrides the workspace root.
+pub const ROOT_ENV: &str = "TODO_PROJECTS_DIR";
+/// Selects a profile when none is given explicitly.
+pub const PROFILE_ENV: &str = "TODO_PROFILE";
+/// Uses a single todo.txt file instead of a workspace, see [`FileStorage`].
+pub const FILE_ENV: &str = "TODO_FILE";
+
+const CONFIG_FILE: &str = "config.toml";
+// Hidden, so it isn't read as a project of the default workspace
+const PROFILES_DIR: &str = ".profiles";
+
+/// Contents of `config.toml` in the config directory:
+///
+/// ```toml
+/// default_profile = "personal"
+/// # Keep the data directory in git, committing on every sync
+/// history = true
+///
+/// [profiles.work]
+/// path = "/home/me/work/projects"
+/// ```
+///
+/// Profiles without a `path` live in `.profiles/<name>` below the default data directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub default_profile: Option<String>,
+    pub history: bool,
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load(config_dir: &Path) -> Result<Config, Error> {
+        let path = config_dir.join(CONFIG_FILE);
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        toml::from_str(&content).map_err(|err| Error::Config {
+            path,
+            message: err.message().to_string(),
+        })
+    }
+}
+
+/// Where to open a [`Projects`] workspace.
+///
+/// The data directory is resolved in this order: an explicit [`file`](Self::file),
+/// [`root`](Self::root) or [`profile`](Self::profile), the [`FILE_ENV`],
+/// [`ROOT_ENV`] and [`PROFILE_ENV`] variables, `default_profile` from the config
+/// file, and finally the platform data directory.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    file: Option<PathBuf>,
+    root: Option<PathBuf>,
+    profile: Option<String>,
+    config_dir: Option<PathBuf>,
+    history: Option<bool>,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    /// Open a single todo.txt file instead of a workspace.
+    pub fn file(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    pub fn root(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.root = Some(path.into());
+        self
+    }
+
+    pub fn profile(&mut self, name: impl Into<String>) -> &mut Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    pub fn config_dir(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.config_dir = Some(path.into());
+        self
+    }
+
+    /// Track the workspace in git, overriding `history` from the config file.
+    pub fn history(&mut self, enabled: bool) -> &mut Self {
+        self.history = Some(enabled);
+        self
+    }
+
+    pub fn open(&self) -> Result<Projects, Error> {
+        let dirs = directories::ProjectDirs::from("com", "Softshag", "Projects");
+        let dirs = || {
+            dirs.as_ref().ok_or_else(|| {
+                Error::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "No home directory found",
+                ))
+            })
+        };
+
+        let config_dir = match &self.config_dir {
+            Some(config_dir) => config_dir.clone(),
+            None => dirs()?.config_local_dir().to_path_buf(),
+        };
+
+        let config = Config::load(&config_dir)?;
+
+        let explicit = self.root.is_some() || self.profile.is_some();
+        let file = match &self.file {
+            Some(file) => Some(file.clone()),
+            None if !explicit => env(FILE_ENV).map(PathBuf::from),
+            None => None,
+        };
+
+        if let Some(file) = file {
+            let storage = Rc::new(FileStorage::open(file));
+            return Projects::load(storage, config_dir, None);
+        }
+
+        let profile = match (&self.root, &self.profile) {
+            (Some(_), _) => None,
+            (None, Some(profile)) => Some(profile.clone()),
+            (None, None) if env(ROOT_ENV).is_some() => None,
+            (None, None) => env(PROFILE_ENV).or_else(|| config.default_profile.clone()),
+        };
+
+        let data_dir = if let Some(root) = &self.root {
+            root.clone()
+        } else if let Some(profile) = &profile {
+            if !is_name(profile) {
+                return Err(Error::InvalidName(profile.clone()));
+            }
+
+            match config.profiles.get(profile).and_then(|m| m.path.clone()) {
+                Some(path) => path,
+                None => dirs()?.data_local_dir().join(PROFILES_DIR).join(profile),
+            }
+        } else if let Some(root) = env(ROOT_ENV) {
+            PathBuf::from(root)
+        } else {
+            dirs()?.data_local_dir().to_path_buf()
+        };
+
+        let storage: Rc<dyn Storage> = Rc::new(DirStorage::open(data_dir)?);
+        let mut projects = Projects::load(storage, config_dir, profile)?;
+        if self.history.unwrap_or(config.history) {
+            projects.enable_history()?;
+        }
+
+        Ok(projects)
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|m| !m.trim().is_empty())
+}
@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    Error, Location, Part, Storage,
+    fs::{self, Lock},
+    storage::ARCHIVE_DIR,
+};
+
+const TRASH_DIR: &str = ".trash";
+
+/// One directory per project holding `README.md`, `todo.txt` and `project.toml`.
+/// Archived projects live in `.archive`, deleted ones are moved to `.trash`.
+#[derive(Debug, Clone)]
+pub struct DirStorage {
+    root: PathBuf,
+}
+
+impl DirStorage {
+    pub fn open(root: impl Into<PathBuf>) -> Result<DirStorage, Error> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(DirStorage { root })
+    }
+
+    fn dir(&self, project: &Location) -> PathBuf {
+        self.root.join(project.path())
+    }
+}
+
+impl Storage for DirStorage {
+    fn list(&self) -> Result<Vec<Location>, Error> {
+        let mut projects = Vec::default();
+
+        for (dir, archived) in [
+            (self.root.clone(), false),
+            (self.root.join(ARCHIVE_DIR), true),
+        ] {
+            if archived && !dir.exists() {
+                continue;
+            }
+
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_dir()
+                    || entry.file_name().to_string_lossy().starts_with('.')
+                {
+                    continue;
+                }
+
+                // Names that aren't UTF-8 are skipped as invalid by the caller
+                let name = entry.file_name().to_string_lossy().into_owned();
+                projects.push(Location::new(name, archived));
+            }
+        }
+
+        Ok(projects)
+    }
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error> {
+        Ok(fs::read_optional(
+            &self.dir(project).join(part.file_name()),
+        )?)
+    }
+
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error> {
+        let dir = self.dir(project);
+        std::fs::create_dir_all(&dir)?;
+        fs::write_atomic(&dir.join(part.file_name()), content.as_bytes())?;
+        Ok(())
+    }
+
+    fn modified(&self, project: &Location) -> Option<DateTime<Utc>> {
+        self.part_modified(project, Part::Todos)
+    }
+
+    fn part_modified(&self, project: &Location, part: Part) -> Option<DateTime<Utc>> {
+        let path = self.dir(project).join(part.file_name());
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        Some(modified.into())
+    }
+
+    fn lock(&self, project: &Location) -> Result<Lock, Error> {
+        let dir = self.dir(project);
+        std::fs::create_dir_all(&dir)?;
+        Ok(Lock::dir(&dir)?)
+    }
+
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        // Named like the other stores do, rather than by path
+        if self.dir(to).exists() {
+            return Err(Error::AlreadyExists(to.name.clone()));
+        }
+        move_dir(&self.dir(from), &self.dir(to))
+    }
+
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error> {
+        let dir = self.dir(project);
+        if !dir.exists() {
+            return Ok(None);
+        }
+
+        let stamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+        let trash = self
+            .root
+            .join(TRASH_DIR)
+            .join(format!("{}-{stamp}", project.name));
+        move_dir(&dir, &trash)?;
+
+        Ok(Some(trash))
+    }
+
+    fn path(&self, project: &Location, part: Part) -> PathBuf {
+        self.dir(project).join(part.file_name())
+    }
+
+    fn root(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+}
+
+/// Projects that were never written have nothing to move.
+fn move_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    if !from.exists() {
+        return Ok(());
+    }
+
+    if to.exists() {
+        return Err(Error::AlreadyExists(to.display().to_string()));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::rename(from, to)?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    AlreadyExists(String),
+    NotFound(String),
+    InvalidName(String),
+    Conflict(String),
+    NoProject,
+    NotLinked(String),
+    Git(String),
+    HistoryDisabled,
+    Unsupported(&'static str),
+    Database(String),
+    TemplateNotFound(String),
+    InvalidBundle(String),
+    Watch(String),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            Self::Config { path, message } => write!(f, "{}: {message}", path.display()),
+            Self::AlreadyExists(name) => write!(f, "Project already exists: {name}"),
+            Self::NotFound(name) => write!(f, "Project not found: {name}"),
+            Self::InvalidName(name) => write!(f, "Invalid project name: {name:?}"),
+            Self::Conflict(name) => write!(f, "Project was changed on disk: {name}"),
+            Self::NoProject => write!(f, "Todo has no +project"),
+            Self::NotLinked(name) => write!(f, "Project has no linked directories: {name}"),
+            Self::Git(message) => write!(f, "git: {message}"),
+            Self::HistoryDisabled => write!(f, "History is not enabled for this workspace"),
+            Self::Unsupported(what) => write!(f, "Not supported by this storage: {what}"),
+            Self::Database(message) => write!(f, "database: {message}"),
+            Self::TemplateNotFound(name) => write!(f, "Template not found: {name}"),
+            Self::InvalidBundle(message) => write!(f, "Invalid bundle: {message}"),
+            Self::Watch(message) => write!(f, "watch: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use todotxt::{Todo, parser::is_name};
+
+use crate::{
+    Error, Location, Part, Storage,
+    fs::{self, Lock},
+    projects::retag,
+};
+
+/// A single classic `todo.txt` where projects are just `+tags`.
+///
+/// A todo belongs to its first `+project`. Lines without one are kept as is but
+/// aren't part of any project. There are no READMEs, `done.txt`, metadata or archive.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn open(path: impl Into<PathBuf>) -> FileStorage {
+        FileStorage { path: path.into() }
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.path
+    }
+
+    fn lines(&self) -> Result<Vec<String>, Error> {
+        Ok(fs::read_optional(&self.path)?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn save(&self, lines: &[String]) -> Result<(), Error> {
+        let mut content = String::default();
+        for line in lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+        fs::write_atomic(&self.path, content.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Storage for FileStorage {
+    fn list(&self) -> Result<Vec<Location>, Error> {
+        let mut projects = Vec::<Location>::default();
+        for line in self.lines()? {
+            if let Some(owner) = owner(&line)
+                && is_name(&owner)
+                && !projects.iter().any(|m| m.name == owner)
+            {
+                projects.push(Location::new(owner, false));
+            }
+        }
+        Ok(projects)
+    }
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error> {
+        if part != Part::Todos || project.archived {
+            return Ok(String::default());
+        }
+
+        let mut content = String::default();
+        for line in self.lines()? {
+            if owner(&line).as_deref() == Some(project.name.as_str()) {
+                content.push_str(&line);
+                content.push('\n');
+            }
+        }
+        Ok(content)
+    }
+
+    /// Replaces the project's lines, keeping them where the first one was.
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error> {
+        match part {
+            Part::Todos if !project.archived => {}
+            // Nothing to store, like default metadata
+            _ if content.trim().is_empty() => return Ok(()),
+            Part::Description => return Err(Error::Unsupported("project descriptions")),
+            Part::Metadata => return Err(Error::Unsupported("project metadata")),
+            Part::Done => return Err(Error::Unsupported("done.txt")),
+            Part::Todos => return Err(Error::Unsupported("archived projects")),
+        }
+
+        let mut lines = Vec::default();
+        let mut new = Some(content.lines().map(str::to_string));
+
+        for line in self.lines()? {
+            if owner(&line).as_deref() != Some(project.name.as_str()) {
+                lines.push(line);
+            } else if let Some(new) = new.take() {
+                lines.extend(new);
+            }
+        }
+
+        if let Some(new) = new {
+            lines.extend(new);
+        }
+
+        self.save(&lines)
+    }
+
+    fn modified(&self, _project: &Location) -> Option<DateTime<Utc>> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()?;
+        Some(modified.into())
+    }
+
+    fn lock(&self, _project: &Location) -> Result<Lock, Error> {
+        Ok(Lock::file(&self.path)?)
+    }
+
+    /// Retags the todos owned by `from`.
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        if from.archived || to.archived {
+            return Err(Error::Unsupported("archived projects"));
+        }
+
+        let mut lines = self.lines()?;
+        if lines
+            .iter()
+            .any(|m| owner(m).as_deref() == Some(to.name.as_str()))
+        {
+            return Err(Error::AlreadyExists(to.name.clone()));
+        }
+
+        for line in &mut lines {
+            if let Some(mut todo) = parse(line)
+                && todo.projects.first() == Some(&from.name)
+            {
+                retag(&mut todo, &from.name, &to.name);
+                *line = todo.to_string();
+            }
+        }
+
+        self.save(&lines)
+    }
+
+    /// Drops the todos owned by the project. Nothing is kept.
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error> {
+        let mut lines = self.lines()?;
+        lines.retain(|m| owner(m).as_deref() != Some(project.name.as_str()));
+        self.save(&lines)?;
+        Ok(None)
+    }
+
+    fn path(&self, _project: &Location, _part: Part) -> PathBuf {
+        self.path.clone()
+    }
+
+    fn is_shared(&self) -> bool {
+        true
+    }
+}
+
+fn parse(line: &str) -> Option<Todo> {
+    todotxt::parser::parse(line)
+        .ok()
+        .and_then(|m| Todo::from(m).ok())
+}
+
+fn owner(line: &str) -> Option<String> {
+    parse(line)?.projects.into_iter().next()
+}
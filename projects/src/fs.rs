@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const LOCK_FILE: &str = ".lock";
+
+/// Write `contents` to a temporary file next to `path` and rename it into place,
+/// so readers only ever see the old or the new file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = tmp_path(path);
+
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return result;
+    }
+
+    // Persist the rename itself. Directories can't be opened for syncing on every platform
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Read a file, treating a missing file as empty.
+pub fn read_optional(path: &Path) -> io::Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|m| m.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
+
+/// Advisory lock held until dropped, see [`Storage::lock`](crate::Storage::lock).
+///
+/// Other processes going through [`Lock`] block until it is released.
+pub struct Lock {
+    _file: Option<File>,
+}
+
+impl Lock {
+    /// Lock a directory through a `.lock` file inside it.
+    pub fn dir(dir: &Path) -> io::Result<Lock> {
+        Lock::acquire(&dir.join(LOCK_FILE))
+    }
+
+    /// Lock a file through a `.<name>.lock` file next to it. The file itself
+    /// can't be locked since [`write_atomic`] replaces it.
+    pub fn file(path: &Path) -> io::Result<Lock> {
+        let name = path
+            .file_name()
+            .map(|m| m.to_string_lossy())
+            .unwrap_or_default();
+        Lock::acquire(&path.with_file_name(format!(".{name}.lock")))
+    }
+
+    /// For stores that don't need locking.
+    pub fn none() -> Lock {
+        Lock { _file: None }
+    }
+
+    fn acquire(path: &Path) -> io::Result<Lock> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        file.lock()?;
+        Ok(Lock { _file: Some(file) })
+    }
+}
+
+/// Three-way merge of line based files.
+///
+/// Starts from `ours` and applies the lines `theirs` removed from and added to `base`.
+/// Lines added on both sides are only kept once. Returns `None` if both sides
+/// changed the same line of `base` differently.
+pub fn merge_lines(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    let base_lines = base.lines().collect::<Vec<_>>();
+    let ours_replaced = replaced(&base_lines, &ours.lines().collect::<Vec<_>>());
+    let theirs_replaced = replaced(&base_lines, &theirs.lines().collect::<Vec<_>>());
+    if ours_replaced
+        .iter()
+        .zip(&theirs_replaced)
+        .any(|pair| matches!(pair, (Some(ours), Some(theirs)) if ours != theirs))
+    {
+        return None;
+    }
+
+    let base_count = count_lines(base);
+    let ours_count = count_lines(ours);
+    let theirs_count = count_lines(theirs);
+
+    let diff = |a: &HashMap<&str, usize>, b: &HashMap<&str, usize>, line: &str| {
+        a.get(line)
+            .copied()
+            .unwrap_or_default()
+            .saturating_sub(b.get(line).copied().unwrap_or_default())
+    };
+
+    let mut removed = HashMap::new();
+    for line in base.lines() {
+        removed
+            .entry(line)
+            .or_insert_with(|| diff(&base_count, &theirs_count, line));
+    }
+
+    let mut added = HashMap::new();
+    for line in theirs.lines() {
+        added.entry(line).or_insert_with(|| {
+            let theirs_added = diff(&theirs_count, &base_count, line);
+            let ours_added = diff(&ours_count, &base_count, line);
+            theirs_added.saturating_sub(ours_added)
+        });
+    }
+
+    let mut out = String::with_capacity(ours.len().max(theirs.len()));
+
+    for line in ours.lines() {
+        match removed.get_mut(line) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+
+    for line in theirs.lines() {
+        if let Some(count) = added.get_mut(line)
+            && *count > 0
+        {
+            *count -= 1;
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    Some(out)
+}
+
+/// For every line of `base`, the lines that replaced it in `other`,
+/// if it was changed rather than kept or removed.
+fn replaced<'a>(base: &[&str], other: &[&'a str]) -> Vec<Option<Vec<&'a str>>> {
+    // Longest common subsequence of the lines after `i` and `j`
+    let mut common = vec![vec![0usize; other.len() + 1]; base.len() + 1];
+    for i in (0..base.len()).rev() {
+        for j in (0..other.len()).rev() {
+            common[i][j] = if base[i] == other[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut replaced = vec![None; base.len()];
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (Vec::default(), Vec::default());
+
+    loop {
+        let keep = i < base.len() && j < other.len() && base[i] == other[j];
+        if keep || (i == base.len() && j == other.len()) {
+            if !added.is_empty() {
+                for idx in removed.drain(..) {
+                    replaced[idx] = Some(added.clone());
+                }
+            }
+            removed.clear();
+            added.clear();
+
+            if !keep {
+                break;
+            }
+            i += 1;
+            j += 1;
+        } else if j == other.len() || (i < base.len() && common[i + 1][j] >= common[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(other[j]);
+            j += 1;
+        }
+    }
+
+    replaced
+}
+
+pub fn count_lines(input: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for line in input.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts
+}
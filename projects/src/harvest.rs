@@ -0,0 +1,220 @@
+//! Turns `TODO:`, `FIXME:` and `XXX:` comments in a project's linked directories into todos.
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use ignore::WalkBuilder;
+use todotxt::{
+    Clock, Todo, Value,
+    parser::{parse, to_description},
+};
+
+use crate::{Error, Projects, Storage};
+
+pub const FILE_KEY: &str = "file";
+pub const LINE_KEY: &str = "line";
+
+const MARKERS: [&str; 3] = ["TODO:", "FIXME:", "XXX:"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The scanned directory.
+    pub root: PathBuf,
+    /// Relative to [`root`](Self::root).
+    pub path: PathBuf,
+    pub line: usize,
+    pub marker: &'static str,
+    pub text: String,
+}
+
+impl Comment {
+    /// Includes the root, so the same path in two linked directories is two files.
+    fn file(&self) -> String {
+        self.root
+            .join(&self.path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn to_todo(&self, project: &str) -> Todo {
+        let mut todo = new_todo(&self.text, project);
+        todo.values
+            .insert(FILE_KEY.to_string(), vec![Value::String(self.file())]);
+        todo.values
+            .insert(LINE_KEY.to_string(), vec![Value::Int(self.line as i64)]);
+        todo
+    }
+}
+
+/// A todo owned by `project` from free text, parsed as todo.txt when that reads
+/// back the same and [escaped](to_description) otherwise.
+pub(crate) fn new_todo(text: &str, project: &str) -> Todo {
+    let own = |mut todo: Todo| {
+        todo.projects.retain(|m| m != project);
+        todo.projects.insert(0, project.to_string());
+        todo
+    };
+
+    parse(text)
+        .ok()
+        .and_then(|m| Todo::from(m).ok())
+        // A leading `x` in free text doesn't complete anything
+        .filter(|m| !m.done)
+        .map(own)
+        .filter(|m| parse(&m.to_string()).is_ok_and(|p| Todo::from(p).ok().as_ref() == Some(m)))
+        .unwrap_or_else(|| {
+            own(Todo {
+                description: to_description(text),
+                ..Default::default()
+            })
+        })
+}
+
+/// Outcome of [`Projects::harvest`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Harvest {
+    pub added: usize,
+    pub moved: usize,
+    pub completed: usize,
+    pub reopened: usize,
+}
+
+/// Find marker comments below `root`, skipping hidden and ignored files.
+pub fn scan(root: &Path) -> Result<Vec<Comment>, Error> {
+    let mut comments = Vec::default();
+
+    for entry in WalkBuilder::new(root).require_git(false).build() {
+        let entry = entry.map_err(|err| Error::Io(io::Error::other(err)))?;
+        if !entry.file_type().is_some_and(|m| m.is_file()) {
+            continue;
+        }
+
+        // Binary and other non UTF-8 files
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+
+        let path = entry.path().strip_prefix(root).unwrap_or(entry.path());
+
+        for (idx, line) in content.lines().enumerate() {
+            if let Some((marker, text)) = find_marker(line) {
+                comments.push(Comment {
+                    root: root.to_path_buf(),
+                    path: path.to_path_buf(),
+                    line: idx + 1,
+                    marker,
+                    text,
+                });
+            }
+        }
+    }
+
+    Ok(comments)
+}
+
+fn find_marker(line: &str) -> Option<(&'static str, String)> {
+    let (idx, marker) = MARKERS
+        .iter()
+        .filter_map(|marker| {
+            line.match_indices(marker)
+                // Not part of a longer word, like `AUTODO:`
+                .find(|(idx, _)| {
+                    !line[..*idx]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                })
+                .map(|(idx, _)| (idx, *marker))
+        })
+        .min_by_key(|(idx, _)| *idx)?;
+
+    let text = line[idx + marker.len()..]
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!text.is_empty()).then_some((marker, text))
+}
+
+impl<S: Storage + ?Sized> Projects<S> {
+    /// Scan the directories linked to a project and reconcile its todos with the
+    /// comments found: new comments are added, moved ones get their `line:` updated,
+    /// todos whose comment is gone are completed and those whose comment is back reopened.
+    pub fn harvest(&mut self, name: &str, clock: &impl Clock) -> Result<Harvest, Error> {
+        let roots = self.links().paths(name).to_vec();
+        if roots.is_empty() {
+            return Err(Error::NotLinked(name.to_string()));
+        }
+
+        let mut comments = Vec::default();
+        for root in &roots {
+            comments.extend(scan(root)?);
+        }
+
+        let project = self.get_mut(name)?;
+        let mut harvest = Harvest::default();
+        let mut seen = vec![false; project.todos()?.len()];
+        let mut added = Vec::default();
+
+        for comment in comments {
+            let todo = comment.to_todo(name);
+            let file = Value::String(comment.file());
+
+            let matching = project
+                .todos()?
+                .iter()
+                .enumerate()
+                .filter(|(idx, m)| {
+                    !seen[*idx]
+                        && m.get(FILE_KEY) == Some(&file)
+                        && m.description == todo.description
+                })
+                .map(|(idx, m)| (idx, m.done))
+                .collect::<Vec<_>>();
+
+            // An open todo for the comment wins over one completed earlier
+            let existing = matching.iter().find(|(_, done)| !done).or(matching.first());
+
+            let Some(&(idx, done)) = existing else {
+                added.push(todo);
+                continue;
+            };
+
+            seen[idx] = true;
+            let existing = project.todos_mut()?.get_mut(idx).unwrap();
+
+            if done {
+                existing.reopen();
+                harvest.reopened += 1;
+            }
+
+            let line = Value::Int(comment.line as i64);
+            if existing.get(LINE_KEY) != Some(&line) {
+                existing.values.insert(LINE_KEY.to_string(), vec![line]);
+                harvest.moved += 1;
+            }
+        }
+
+        for (idx, seen) in seen.into_iter().enumerate() {
+            let todo = project.todos()?.get(idx).unwrap();
+            if seen || todo.done || todo.get(FILE_KEY).is_none() {
+                continue;
+            }
+
+            project.todos_mut()?.get_mut(idx).unwrap().complete(clock);
+            harvest.completed += 1;
+        }
+
+        for mut todo in added {
+            todo.created = Some(clock.today());
+            project.todos_mut()?.create_todo(todo);
+            harvest.added += 1;
+        }
+
+        Ok(harvest)
+    }
+}
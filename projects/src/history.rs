@@ -0,0 +1,262 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use chrono::{DateTime, FixedOffset};
+use todotxt::{Todo, parser::parse};
+
+use crate::Error;
+
+const GIT_DIR: &str = ".git";
+const GITIGNORE_FILE: &str = ".gitignore";
+const GITIGNORE: &str = ".lock\n.*.tmp\n.trash/\n.cache.toml\n.index.toml\n";
+
+/// A commit touching a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub id: String,
+    pub date: DateTime<FixedOffset>,
+    pub message: String,
+}
+
+impl Revision {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+}
+
+/// Thin wrapper around the `git` command line for a data directory.
+#[derive(Debug, Clone)]
+pub(crate) struct Git {
+    dir: PathBuf,
+}
+
+impl Git {
+    pub fn open(dir: &Path) -> Option<Git> {
+        dir.join(GIT_DIR).exists().then(|| Git {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn init(dir: &Path) -> Result<Git, Error> {
+        if let Some(git) = Git::open(dir) {
+            return Ok(git);
+        }
+
+        let git = Git {
+            dir: dir.to_path_buf(),
+        };
+        git.run(["init", "-q"])?;
+        std::fs::write(dir.join(GITIGNORE_FILE), GITIGNORE)?;
+
+        // Start out with whatever is already in the directory
+        git.commit(&[Path::new(".")], "track projects")?;
+
+        Ok(git)
+    }
+
+    /// Commit the current state of `paths`. Returns false when nothing changed.
+    pub fn commit(&self, paths: &[&Path], message: &str) -> Result<bool, Error> {
+        let mut add = vec![OsStr::new("add"), OsStr::new("-A"), OsStr::new("--")];
+        add.extend(paths.iter().map(|m| m.as_os_str()));
+        self.run(add)?;
+
+        let staged = self.command(["diff", "--cached", "--quiet"]).status()?;
+        if staged.success() {
+            return Ok(false);
+        }
+
+        let mut commit = self.command(["commit", "-q", "-m", message]);
+        // Don't fail on machines without a configured identity
+        let has_identity = self
+            .command(["config", "user.email"])
+            .output()?
+            .status
+            .success();
+        if !has_identity {
+            commit.env("GIT_AUTHOR_NAME", "todo");
+            commit.env("GIT_AUTHOR_EMAIL", "todo@localhost");
+            commit.env("GIT_COMMITTER_NAME", "todo");
+            commit.env("GIT_COMMITTER_EMAIL", "todo@localhost");
+        }
+        check(commit.output()?)?;
+
+        Ok(true)
+    }
+
+    /// Commits touching any of `paths`, newest first.
+    pub fn log(&self, paths: &[&Path]) -> Result<Vec<Revision>, Error> {
+        let mut log = self.command(["log", "--format=%H%x1f%cI%x1f%s", "--"]);
+        log.args(paths);
+        let output = check(log.output()?)?;
+
+        output
+            .lines()
+            .map(|line| {
+                let mut parts = line.splitn(3, '\x1f');
+                let (Some(id), Some(date), Some(message)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(Error::Git(format!("unexpected log line: {line}")));
+                };
+
+                Ok(Revision {
+                    id: id.to_string(),
+                    date: DateTime::parse_from_rfc3339(date)
+                        .map_err(|err| Error::Git(err.to_string()))?,
+                    message: message.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Resolve `rev` to a full commit id.
+    pub fn resolve(&self, rev: &str) -> Result<String, Error> {
+        if rev.starts_with('-') {
+            return Err(Error::Git(format!("invalid revision: {rev}")));
+        }
+
+        let output = self
+            .command(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{rev}^{{commit}}"))
+            .output()?;
+
+        if !output.status.success() {
+            return Err(Error::Git(format!("unknown revision: {rev}")));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Contents of `path` at the resolved commit `id`, `None` if it didn't exist.
+    pub fn read(&self, id: &str, path: &str) -> Result<Option<String>, Error> {
+        let output = self
+            .command(["show"])
+            .arg(format!("{id}:{path}"))
+            .output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    fn run<I, S>(&self, args: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        check(self.command(args).output()?)
+    }
+
+    fn command<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.dir).args(args);
+        command
+    }
+}
+
+fn check(output: Output) -> Result<String, Error> {
+    if !output.status.success() {
+        return Err(Error::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Summarize the difference between two versions of a project, e.g.
+/// `complete 2 todos in +website`. Todos are compared as parsed, so moved or
+/// reformatted lines don't count. Returns `None` if nothing changed.
+pub(crate) fn describe(
+    project: &str,
+    old_todos: &str,
+    new_todos: &str,
+    readme_changed: bool,
+    metadata_changed: bool,
+) -> Option<String> {
+    let (mut removed, old_other) = todos(old_todos);
+    let (new, new_other) = todos(new_todos);
+
+    let mut added = Vec::new();
+    for todo in new {
+        match removed.iter().position(|m| *m == todo) {
+            Some(idx) => {
+                removed.swap_remove(idx);
+            }
+            None => added.push(todo),
+        }
+    }
+
+    let (mut created, mut completed, mut reopened, mut edited) = (0, 0, 0, 0);
+
+    for todo in added {
+        let Some(idx) = removed
+            .iter()
+            .position(|m| m.description == todo.description)
+        else {
+            created += 1;
+            continue;
+        };
+
+        let old = removed.swap_remove(idx);
+        match (old.done, todo.done) {
+            (false, true) => completed += 1,
+            (true, false) => reopened += 1,
+            _ => edited += 1,
+        }
+    }
+
+    let mut parts = [
+        ("add", created),
+        ("complete", completed),
+        ("reopen", reopened),
+        ("edit", edited),
+        ("remove", removed.len()),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(verb, count)| match count {
+        1 => format!("{verb} 1 todo"),
+        n => format!("{verb} {n} todos"),
+    })
+    .collect::<Vec<_>>();
+
+    if readme_changed {
+        parts.push("update README".to_string());
+    }
+
+    if metadata_changed {
+        parts.push("update metadata".to_string());
+    }
+
+    if parts.is_empty() {
+        // Only lines we can't parse changed
+        return (old_other != new_other).then(|| format!("update +{project}"));
+    }
+
+    Some(format!("{} in +{project}", parts.join(", ")))
+}
+
+/// The todos in `text`, and the sorted lines that aren't todos.
+fn todos(text: &str) -> (Vec<Todo>, Vec<&str>) {
+    let mut todos = Vec::new();
+    let mut other = Vec::new();
+    for line in text.lines().filter(|m| !m.trim().is_empty()) {
+        match todo(line) {
+            Some(todo) => todos.push(todo),
+            None => other.push(line),
+        }
+    }
+    other.sort_unstable();
+    (todos, other)
+}
+
+fn todo(line: &str) -> Option<Todo> {
+    parse(line).ok().and_then(|m| Todo::from(m).ok())
+}
@@ -1,3 +1,45 @@
+mod bundle;
+mod cache;
+pub mod checklist;
+mod config;
+mod dir;
+mod error;
+mod file;
+mod fs;
+pub mod harvest;
+mod history;
+mod links;
+mod memory;
+mod metadata;
 mod projects;
+pub mod report;
+mod search;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod storage;
+mod template;
+#[cfg(feature = "watch")]
+mod watch;
 
-pub use self::projects::*;
+pub use self::{
+    bundle::ImportMode,
+    cache::Summary,
+    config::*,
+    dir::*,
+    error::*,
+    file::*,
+    fs::Lock,
+    history::Revision,
+    links::*,
+    memory::*,
+    metadata::*,
+    projects::*,
+    search::{Field, Hit, SearchResults},
+    storage::*,
+    template::Template,
+};
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::*;
+#[cfg(feature = "watch")]
+pub use self::watch::Watcher;
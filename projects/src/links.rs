@@ -0,0 +1,143 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use todotxt::parser::is_name;
+
+use crate::{Error, fs};
+
+/// Marks a directory tree as belonging to a project. Holds the project name,
+/// or nothing to use the name of the directory it is in.
+pub const MARKER_FILE: &str = ".todo";
+
+const LINKS_FILE: &str = "links.toml";
+
+/// Directories linked to projects, kept in `links.toml` in the config directory
+/// since checkouts live in different places on every machine:
+///
+/// ```toml
+/// [projects]
+/// website = ["/home/me/src/website"]
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Links {
+    projects: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl Links {
+    pub fn load(config_dir: &Path) -> Result<Links, Error> {
+        let path = config_dir.join(LINKS_FILE);
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Links::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        toml::from_str(&content).map_err(|err| Error::Config {
+            path,
+            message: err.message().to_string(),
+        })
+    }
+
+    pub fn save(&self, config_dir: &Path) -> Result<(), Error> {
+        std::fs::create_dir_all(config_dir)?;
+        let content = toml::to_string(self).expect("links serialize to toml");
+        fs::write_atomic(&config_dir.join(LINKS_FILE), content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn paths(&self, project: &str) -> &[PathBuf] {
+        self.projects.get(project).map_or(&[], |m| m.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[PathBuf])> {
+        self.projects
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    /// Returns false if the path already was linked.
+    pub fn link(&mut self, project: &str, path: PathBuf) -> bool {
+        let paths = self.projects.entry(project.to_string()).or_default();
+        if paths.contains(&path) {
+            return false;
+        }
+        paths.push(path);
+        true
+    }
+
+    /// Returns false if the path wasn't linked.
+    pub fn unlink(&mut self, project: &str, path: &Path) -> bool {
+        let Some(paths) = self.projects.get_mut(project) else {
+            return false;
+        };
+
+        let len = paths.len();
+        paths.retain(|m| m != path);
+        let removed = paths.len() != len;
+
+        if paths.is_empty() {
+            self.projects.remove(project);
+        }
+
+        removed
+    }
+
+    pub(crate) fn rename(&mut self, from: &str, to: &str) {
+        if let Some(paths) = self.projects.remove(from) {
+            for path in paths {
+                self.link(to, path);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, project: &str) -> bool {
+        self.projects.remove(project).is_some()
+    }
+
+    /// The project linked to exactly `dir`.
+    fn linked(&self, dir: &Path) -> Option<&str> {
+        self.projects
+            .iter()
+            .find(|(_, paths)| paths.iter().any(|m| m == dir))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Find the project for `dir` by walking up its ancestors, the way git finds
+    /// its repository. The closest `.todo` marker or linked directory wins.
+    pub fn discover(&self, dir: &Path) -> Option<String> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+        for ancestor in dir.ancestors() {
+            if let Some(name) = marker(ancestor) {
+                return Some(name);
+            }
+
+            if let Some(name) = self.linked(ancestor) {
+                return Some(name.to_string());
+            }
+        }
+
+        None
+    }
+}
+
+fn marker(dir: &Path) -> Option<String> {
+    let path = dir.join(MARKER_FILE);
+    if !path.is_file() {
+        return None;
+    }
+
+    let content = std::fs::read_to_string(path).ok()?;
+    let name = match content.lines().map(str::trim).find(|m| !m.is_empty()) {
+        Some(name) => name.trim_start_matches('+'),
+        None => dir.file_name()?.to_str()?,
+    };
+
+    is_name(name).then(|| name.to_string())
+}
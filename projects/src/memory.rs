@@ -0,0 +1,73 @@
+use std::{cell::RefCell, collections::BTreeMap, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::{Error, Location, Part, Storage, fs::Lock};
+
+#[derive(Debug, Default, Clone)]
+struct Entry {
+    parts: BTreeMap<Part, (String, DateTime<Utc>)>,
+}
+
+/// Keeps projects in memory, for tests and throwaway workspaces.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    projects: RefCell<BTreeMap<Location, Entry>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn list(&self) -> Result<Vec<Location>, Error> {
+        Ok(self.projects.borrow().keys().cloned().collect())
+    }
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error> {
+        Ok(self
+            .projects
+            .borrow()
+            .get(project)
+            .and_then(|m| m.parts.get(&part))
+            .map(|(content, _)| content.clone())
+            .unwrap_or_default())
+    }
+
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error> {
+        let mut projects = self.projects.borrow_mut();
+        let entry = projects.entry(project.clone()).or_default();
+        entry.parts.insert(part, (content.to_string(), Utc::now()));
+        Ok(())
+    }
+
+    fn modified(&self, project: &Location) -> Option<DateTime<Utc>> {
+        self.part_modified(project, Part::Todos)
+    }
+
+    fn part_modified(&self, project: &Location, part: Part) -> Option<DateTime<Utc>> {
+        Some(self.projects.borrow().get(project)?.parts.get(&part)?.1)
+    }
+
+    fn lock(&self, _project: &Location) -> Result<Lock, Error> {
+        Ok(Lock::none())
+    }
+
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        let mut projects = self.projects.borrow_mut();
+        if projects.contains_key(to) {
+            return Err(Error::AlreadyExists(to.name.clone()));
+        }
+        if let Some(entry) = projects.remove(from) {
+            projects.insert(to.clone(), entry);
+        }
+        Ok(())
+    }
+
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error> {
+        self.projects.borrow_mut().remove(project);
+        Ok(None)
+    }
+}
@@ -0,0 +1,130 @@
+use core::fmt;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Project, Storage};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    Active,
+    Paused,
+    Done,
+    Archived,
+}
+
+impl Status {
+    pub const ALL: [Status; 4] = [
+        Status::Active,
+        Status::Paused,
+        Status::Done,
+        Status::Archived,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Active => "active",
+            Status::Paused => "paused",
+            Status::Done => "done",
+            Status::Archived => "archived",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Status> {
+        Status::ALL.into_iter().find(|m| m.as_str() == name)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Contents of `project.toml` next to a project's README:
+///
+/// ```toml
+/// status = "paused"
+/// owner = "rasmus"
+/// start = 2024-03-01
+/// target = 2024-06-30
+/// tags = ["client", "web"]
+/// color = "#3366ff"
+/// checklist = true
+/// ```
+///
+/// Every field is optional; projects without the file are active.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Keep `- [ ]` checklists in the README in step with the todos,
+    /// see [`Project::sync_checklist`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub checklist: bool,
+}
+
+impl Metadata {
+    pub(crate) fn parse(path: &Path, input: &str) -> Result<Metadata, Error> {
+        toml::from_str(input).map_err(|err| Error::Parse {
+            path: path.to_path_buf(),
+            line: err
+                .span()
+                .map(|span| input[..span.start].matches('\n').count() + 1)
+                .unwrap_or(1),
+            message: err.message().to_string(),
+        })
+    }
+
+    pub(crate) fn to_toml(&self) -> String {
+        toml::to_string(self).expect("metadata serializes to toml")
+    }
+}
+
+/// Selects projects by their [`Metadata`]. Unset fields match everything.
+///
+/// Archived projects are skipped unless asked for by `status` or `archived`.
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub status: Option<Status>,
+    pub owner: Option<String>,
+    pub tag: Option<String>,
+    /// Only projects with a target date on or before this date.
+    pub target_before: Option<NaiveDate>,
+    pub archived: bool,
+}
+
+impl Filter {
+    pub fn matches<S: Storage + ?Sized>(&self, project: &Project<S>) -> Result<bool, Error> {
+        let metadata = project.metadata()?;
+
+        let status = match self.status {
+            Some(status) => metadata.status == status,
+            None => self.archived || metadata.status != Status::Archived,
+        };
+
+        let owner = self.owner.is_none() || metadata.owner == self.owner;
+        let tag = self
+            .tag
+            .as_ref()
+            .is_none_or(|tag| metadata.tags.contains(tag));
+        let target = self
+            .target_before
+            .is_none_or(|date| metadata.target.is_some_and(|target| target <= date));
+
+        Ok(status && owner && tag && target)
+    }
+}
@@ -1,91 +1,351 @@
 use std::{
-    io,
+    cell::OnceCell,
+    collections::HashMap,
     path::{Path, PathBuf},
+    rc::Rc,
 };
 
-use todotxt::{Collection, Todo};
+use chrono::{DateTime, Utc};
+use todotxt::{
+    Collection, ReadError, Todo,
+    parser::{is_name, parse},
+};
 
-const DESCRIPTION_FILE: &'static str = "README.md";
-const TODOTXT_FILE: &'static str = "todo.txt";
+use crate::{
+    Error, Filter, Links, Location, Metadata, OpenOptions, Part, Revision, Status, Storage,
+    cache::{Cache, Summary},
+    fs,
+    history::{self, Git},
+};
 
-pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+/// What [`Projects::sync`] does when a project changed on disk since it was loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Fail with [`Error::Conflict`] and leave the files untouched.
+    #[default]
+    Refuse,
+    /// Merge the todo lists line by line. A todo changed differently on both sides,
+    /// diverged READMEs or metadata still conflict.
+    Merge,
+}
 
-#[derive(Debug)]
-pub enum Error {
-    Io(io::Error),
+/// What [`Projects::refresh`] found changed in the storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// A project showed up.
+    Added(String),
+    /// A project went away and was dropped.
+    Removed(String),
+    /// A project without unsaved changes changed and is read again on next access.
+    Reloaded(String),
+    /// A project with unsaved changes changed or went away. Nothing is dropped;
+    /// [`Projects::sync`] merges or refuses according to the [`ConflictStrategy`].
+    Conflict(String),
 }
 
-impl core::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Io(err) => write!(f, "{err}"),
+/// README and todos, loaded on first access.
+struct Content {
+    description: String,
+    todos: Collection,
+    // Text as last read from or written to storage
+    base_description: String,
+    base_todos: String,
+}
+
+impl Content {
+    fn read(storage: &(impl Storage + ?Sized), location: &Location) -> Result<Content, Error> {
+        let description = storage.read(location, Part::Description)?;
+        let todos = storage.read(location, Part::Todos)?;
+        Ok(Content {
+            description: description.clone(),
+            todos: parse_todos(&storage.path(location, Part::Todos), &todos)?,
+            base_description: description,
+            base_todos: todos,
+        })
+    }
+
+    fn merge(
+        &mut self,
+        name: &str,
+        path: &Path,
+        description: &str,
+        todos: &str,
+    ) -> Result<(), Error> {
+        let ours_changed = self.description != self.base_description;
+        let theirs_changed = description != self.base_description;
+        if theirs_changed && self.description != description {
+            if ours_changed {
+                return Err(Error::Conflict(name.to_string()));
+            }
+            self.description = description.to_string();
+        }
+
+        if todos != self.base_todos {
+            let ours = render_todos(&self.todos, &self.base_todos);
+            let merged = fs::merge_lines(&self.base_todos, &ours, todos)
+                .ok_or_else(|| Error::Conflict(name.to_string()))?;
+            self.todos = parse_todos(path, &merged)?;
         }
+
+        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+/// `project.toml`, loaded separately so filtering on it doesn't read the todos.
+struct Settings {
+    metadata: Metadata,
+    base: String,
+}
 
-impl From<io::Error> for Error {
-    fn from(value: io::Error) -> Self {
-        Error::Io(value)
+impl Settings {
+    fn read(storage: &(impl Storage + ?Sized), location: &Location) -> Result<Settings, Error> {
+        let base = storage.read(location, Part::Metadata)?;
+        Ok(Settings {
+            metadata: Metadata::parse(&storage.path(location, Part::Metadata), &base)?,
+            base,
+        })
+    }
+
+    fn merge(&mut self, name: &str, path: &Path, current: &str) -> Result<(), Error> {
+        let base = Metadata::parse(path, &self.base)?;
+        let theirs = Metadata::parse(path, current)?;
+        if theirs != self.metadata {
+            if self.metadata != base {
+                return Err(Error::Conflict(name.to_string()));
+            }
+            self.metadata = theirs;
+        }
+        Ok(())
     }
-}
 
-impl From<BoxError> for Error {
-    fn from(value: BoxError) -> Self {
-        todo!()
+    /// The file as written by hand unless the metadata actually changed.
+    fn to_toml(&self, path: &Path) -> Result<String, Error> {
+        if self.metadata == Metadata::parse(path, &self.base)? {
+            Ok(self.base.clone())
+        } else {
+            Ok(self.metadata.to_toml())
+        }
     }
 }
 
-pub struct Project {
+/// A project handle. The README, todos and metadata are read on first access,
+/// so a broken file only fails the operations that need it.
+pub struct Project<S: ?Sized = dyn Storage> {
     name: String,
-    description: String,
-    todos: Collection,
+    storage: Rc<S>,
+    in_archive: bool,
+    modified: Option<DateTime<Utc>>,
+    cached: Option<Summary>,
+    content: OnceCell<Content>,
+    settings: OnceCell<Settings>,
     dirty: bool,
 }
 
-impl Project {
-    fn new(name: String) -> Project {
+impl<S: Storage + ?Sized> Project<S> {
+    fn new(name: String, storage: Rc<S>) -> Project<S> {
         Project {
             name,
-            description: String::default(),
-            todos: Collection::default(),
+            storage,
+            in_archive: false,
+            modified: None,
+            cached: None,
+            content: OnceCell::from(Content {
+                description: String::default(),
+                todos: Collection::default(),
+                base_description: String::default(),
+                base_todos: String::default(),
+            }),
+            settings: OnceCell::from(Settings {
+                metadata: Metadata::default(),
+                base: String::default(),
+            }),
             dirty: false,
         }
     }
 
-    fn open(path: &Path) -> Result<Project, Error> {
-        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
-        let description = std::fs::read_to_string(path.join(DESCRIPTION_FILE)).unwrap_or_default();
-        let todos = if let Ok(file) = std::fs::OpenOptions::new()
-            .read(true)
-            .open(path.join(TODOTXT_FILE))
-        {
-            Collection::open_reader(file)?
-        } else {
-            Collection::default()
-        };
+    fn open(storage: Rc<S>, location: Location) -> Result<Project<S>, Error> {
+        if !is_name(&location.name) {
+            return Err(Error::InvalidName(location.name));
+        }
 
         Ok(Project {
-            name,
-            description,
-            todos,
+            modified: storage.modified(&location),
+            name: location.name,
+            storage,
+            in_archive: location.archived,
+            cached: None,
+            content: OnceCell::new(),
+            settings: OnceCell::new(),
             dirty: false,
         })
     }
 
-    fn write(&self, root: &Path) -> Result<(), Error> {
-        let project_path = root.join(&self.name);
-        std::fs::create_dir_all(&project_path)?;
-        std::fs::write(project_path.join(DESCRIPTION_FILE), &self.description)?;
+    fn content(&self) -> Result<&Content, Error> {
+        if let Some(content) = self.content.get() {
+            return Ok(content);
+        }
+        let content = Content::read(&*self.storage, &self.location())?;
+        Ok(self.content.get_or_init(|| content))
+    }
+
+    fn content_mut(&mut self) -> Result<&mut Content, Error> {
+        self.content()?;
+        self.dirty = true;
+        Ok(self.content.get_mut().unwrap())
+    }
+
+    fn settings(&self) -> Result<&Settings, Error> {
+        if let Some(settings) = self.settings.get() {
+            return Ok(settings);
+        }
+        let settings = Settings::read(&*self.storage, &self.location())?;
+        Ok(self.settings.get_or_init(|| settings))
+    }
+
+    fn settings_mut(&mut self) -> Result<&mut Settings, Error> {
+        self.settings()?;
+        self.dirty = true;
+        Ok(self.settings.get_mut().unwrap())
+    }
+
+    /// Write whatever was loaded. Returns a summary of the changes,
+    /// `None` when the files were already up to date.
+    fn write(&mut self, strategy: ConflictStrategy) -> Result<Option<String>, Error> {
+        let location = self.location();
+        let storage = &*self.storage;
 
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(project_path.join(TODOTXT_FILE))?;
+        let _lock = storage.lock(&location)?;
 
-        self.todos.write_writer(&mut file)?;
+        let mut todos_change = None;
+        let mut readme_changed = false;
+        let mut metadata_changed = false;
+
+        if let Some(content) = self.content.get_mut() {
+            let description = storage.read(&location, Part::Description)?;
+            let todos = storage.read(&location, Part::Todos)?;
+
+            if description != content.base_description || todos != content.base_todos {
+                match strategy {
+                    ConflictStrategy::Refuse => return Err(Error::Conflict(self.name.clone())),
+                    ConflictStrategy::Merge => content.merge(
+                        &self.name,
+                        &storage.path(&location, Part::Todos),
+                        &description,
+                        &todos,
+                    )?,
+                }
+            }
+
+            if content.description != description {
+                storage.write(&location, Part::Description, &content.description)?;
+                readme_changed = true;
+            }
+
+            let new_todos =
+                render_todos(&content.todos, &format!("{todos}\n{}", content.base_todos));
+            if new_todos != todos {
+                storage.write(&location, Part::Todos, &new_todos)?;
+                self.modified = storage.modified(&location);
+            }
+
+            content.base_description = content.description.clone();
+            content.base_todos = new_todos.clone();
+            todos_change = Some((todos, new_todos));
+        }
+
+        if let Some(settings) = self.settings.get_mut() {
+            let path = storage.path(&location, Part::Metadata);
+            let current = storage.read(&location, Part::Metadata)?;
+
+            if current != settings.base {
+                match strategy {
+                    ConflictStrategy::Refuse => return Err(Error::Conflict(self.name.clone())),
+                    ConflictStrategy::Merge => settings.merge(&self.name, &path, &current)?,
+                }
+            }
+
+            let new = settings.to_toml(&path)?;
+            if new != current {
+                storage.write(&location, Part::Metadata, &new)?;
+                metadata_changed = true;
+            }
+
+            settings.base = new;
+        }
+
+        self.dirty = false;
+
+        // Nothing to commit if the todos are the same once parsed
+        let (old_todos, new_todos) = todos_change.unwrap_or_default();
+        Ok(history::describe(
+            &self.name,
+            &old_todos,
+            &new_todos,
+            readme_changed,
+            metadata_changed,
+        ))
+    }
+
+    pub(crate) fn location(&self) -> Location {
+        Location::new(&self.name, self.in_archive)
+    }
+
+    /// Location relative to the data directory.
+    fn path(&self) -> PathBuf {
+        self.location().path()
+    }
+
+    /// Whether the storage no longer holds what the loaded parts were read from.
+    fn changed_on_disk(&self) -> Result<bool, Error> {
+        let location = self.location();
+        if let Some(content) = self.content.get()
+            && (self.storage.read(&location, Part::Description)? != content.base_description
+                || self.storage.read(&location, Part::Todos)? != content.base_todos)
+        {
+            return Ok(true);
+        }
+        if let Some(settings) = self.settings.get()
+            && self.storage.read(&location, Part::Metadata)? != settings.base
+        {
+            return Ok(true);
+        }
+        Ok(self.storage.modified(&location) != self.modified)
+    }
+
+    /// Forget whatever was loaded so it is read again on next access.
+    fn reload(&mut self) {
+        self.content = OnceCell::new();
+        self.settings = OnceCell::new();
+        self.modified = self.storage.modified(&self.location());
+        self.dirty = false;
+    }
+
+    /// Re-read what the loaded parts are compared against on write, after the
+    /// storage moved the project. Stores may rewrite a project while moving it.
+    fn rebase(&mut self) -> Result<(), Error> {
+        let location = self.location();
+        if let Some(content) = self.content.get_mut() {
+            content.base_description = self.storage.read(&location, Part::Description)?;
+            content.base_todos = self.storage.read(&location, Part::Todos)?;
+        }
+        if let Some(settings) = self.settings.get_mut() {
+            settings.base = self.storage.read(&location, Part::Metadata)?;
+        }
+        Ok(())
+    }
+
+    /// Replace `+from` with `+to` in every todo.
+    fn retag(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let tagged = self
+            .todos()?
+            .iter()
+            .any(|m| m.projects.iter().any(|p| p == from));
+
+        if tagged {
+            for todo in self.todos_mut()?.iter_mut() {
+                retag(todo, from, to);
+            }
+        }
 
         Ok(())
     }
@@ -94,92 +354,846 @@ impl Project {
         &self.name
     }
 
-    pub fn description(&self) -> &str {
-        &self.description
+    pub fn description(&self) -> Result<&str, Error> {
+        Ok(&self.content()?.description)
     }
 
-    pub fn description_mut(&mut self) -> &mut String {
-        self.dirty = true;
-        &mut self.description
+    pub fn description_mut(&mut self) -> Result<&mut String, Error> {
+        Ok(&mut self.content_mut()?.description)
     }
 
-    pub fn todos(&self) -> &Collection {
-        &self.todos
+    pub fn todos(&self) -> Result<&Collection, Error> {
+        Ok(&self.content()?.todos)
     }
 
-    pub fn todos_mut(&mut self) -> &mut Collection {
-        self.dirty = true;
-        &mut self.todos
+    pub fn todos_mut(&mut self) -> Result<&mut Collection, Error> {
+        Ok(&mut self.content_mut()?.todos)
+    }
+
+    pub fn metadata(&self) -> Result<&Metadata, Error> {
+        Ok(&self.settings()?.metadata)
+    }
+
+    pub fn metadata_mut(&mut self) -> Result<&mut Metadata, Error> {
+        Ok(&mut self.settings_mut()?.metadata)
+    }
+
+    pub fn status(&self) -> Result<Status, Error> {
+        Ok(self.metadata()?.status)
+    }
+
+    pub fn set_status(&mut self, status: Status) -> Result<(), Error> {
+        self.metadata_mut()?.status = status;
+        Ok(())
+    }
+
+    /// Whether the project lives in the archive area, see [`Projects::archive`].
+    pub fn is_archived(&self) -> bool {
+        self.in_archive
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.content.get().is_some()
+    }
+
+    /// Whether there are changes not yet written by [`Projects::sync`].
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// When the todos were last changed on disk.
+    pub fn modified(&self) -> Option<DateTime<Utc>> {
+        self.modified
+    }
+
+    /// When the README was last changed on disk, if the storage knows.
+    pub(crate) fn description_modified(&self) -> Option<DateTime<Utc>> {
+        self.storage
+            .part_modified(&self.location(), Part::Description)
+    }
+
+    /// Todo counts, from the cache when the todos haven't been loaded or changed since.
+    pub fn summary(&self) -> Result<Summary, Error> {
+        if self.content.get().is_none()
+            && let Some(cached) = self.cached
+            && cached.modified == self.modified
+        {
+            return Ok(cached);
+        }
+
+        let todos = self.todos()?;
+        let done = todos.iter().filter(|m| m.done).count();
+
+        Ok(Summary {
+            open: todos.len() - done,
+            done,
+            modified: self.modified,
+        })
     }
 }
 
-pub struct Projects {
-    projects: Vec<Project>,
-    data_dir: PathBuf,
+/// Points at a todo in the project that owns it.
+///
+/// Every todo is stored once, in its owning project, and listed in every project it is tagged with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TodoRef {
+    pub project: String,
+    pub index: usize,
+}
+
+/// What [`Projects::listed`] found.
+#[derive(Debug, Default)]
+pub struct Listed<'a> {
+    pub todos: Vec<(TodoRef, &'a Todo)>,
+    /// Projects that couldn't be read and were left out.
+    pub skipped: Vec<(String, Error)>,
+}
+
+pub struct Projects<S: ?Sized = dyn Storage> {
+    projects: Vec<Project<S>>,
+    storage: Rc<S>,
     config_dir: PathBuf,
+    profile: Option<String>,
+    conflict_strategy: ConflictStrategy,
+    history: Option<Git>,
+    links: Links,
+    cache: Cache,
+    invalid: Vec<String>,
 }
 
 impl Projects {
     pub fn open() -> Result<Projects, Error> {
-        let dirs = directories::ProjectDirs::from("com", "Softshag", "Projects")
-            .expect("Config directory");
-
-        let data_dir = dirs.data_local_dir().to_path_buf();
-        let config_dir = dirs.config_local_dir().to_path_buf();
-        let mut projects = Vec::default();
+        OpenOptions::new().open()
+    }
+}
 
-        std::fs::create_dir_all(&data_dir)?;
+impl<S: Storage> Projects<S> {
+    /// Projects kept in `storage`, with links and other settings read from `config_dir`.
+    pub fn with_storage(storage: S, config_dir: impl Into<PathBuf>) -> Result<Projects<S>, Error> {
+        Projects::load(Rc::new(storage), config_dir.into(), None)
+    }
+}
 
-        let readdir = std::fs::read_dir(&data_dir)?;
+impl<S: Storage + ?Sized> Projects<S> {
+    pub(crate) fn load(
+        storage: Rc<S>,
+        config_dir: PathBuf,
+        profile: Option<String>,
+    ) -> Result<Projects<S>, Error> {
+        let root = storage.root().map(Path::to_path_buf);
+        let history = root.as_deref().and_then(Git::open);
+        let links = Links::load(&config_dir)?;
+        let cache = root.as_deref().map(Cache::load).unwrap_or_default();
 
-        for entry in readdir {
-            let entry = entry?;
-            if !entry.file_type()?.is_dir() {
+        let mut projects = Vec::default();
+        let mut invalid = Vec::default();
+        for location in storage.list()? {
+            // Don't let a stray directory break every command
+            if !is_name(&location.name) {
+                invalid.push(location.name);
                 continue;
             }
-
-            let project = Project::open(&entry.path())?;
+            let mut project = Project::open(storage.clone(), location)?;
+            project.cached = cache.get(&project.name);
             projects.push(project);
         }
 
         Ok(Projects {
             projects,
-            data_dir,
+            storage,
             config_dir,
+            profile,
+            conflict_strategy: ConflictStrategy::default(),
+            history,
+            links,
+            cache,
+            invalid,
         })
     }
 
-    pub fn sync(&self) -> Result<(), Error> {
-        for project in &self.projects {
-            if project.dirty {
-                project.write(&self.data_dir)?;
+    /// Entries in the storage that were skipped because they aren't valid project
+    /// names, e.g. a directory called `Old stuff`.
+    pub fn invalid(&self) -> &[String] {
+        &self.invalid
+    }
+
+    /// The directory projects are kept in, unless the storage isn't directory based.
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.storage.root()
+    }
+
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
+    /// Link a directory to a project so commands run inside it default to the project.
+    /// Returns false if it already was linked.
+    pub fn link(&mut self, name: &str, path: &Path) -> Result<bool, Error> {
+        self.get(name)?;
+
+        let linked = self.links.link(name, path.canonicalize()?);
+        if linked {
+            self.links.save(&self.config_dir)?;
+        }
+        Ok(linked)
+    }
+
+    pub fn unlink(&mut self, name: &str, path: &Path) -> Result<bool, Error> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        let unlinked = self.links.unlink(name, &path);
+        if unlinked {
+            self.links.save(&self.config_dir)?;
+        }
+        Ok(unlinked)
+    }
+
+    /// The project `dir` belongs to, see [`Links::discover`].
+    pub fn discover(&self, dir: &Path) -> Option<String> {
+        self.links.discover(dir)
+    }
+
+    pub fn conflict_strategy(&self) -> ConflictStrategy {
+        self.conflict_strategy
+    }
+
+    pub fn set_conflict_strategy(&mut self, strategy: ConflictStrategy) {
+        self.conflict_strategy = strategy;
+    }
+
+    /// Turn the data directory into a git repository, committing on every [`sync`](Self::sync).
+    /// Workspaces that already are a repository have history enabled when loaded.
+    pub fn enable_history(&mut self) -> Result<(), Error> {
+        if self.history.is_none() {
+            let root = self.storage.root().ok_or(Error::Unsupported("history"))?;
+            self.history = Some(Git::init(root)?);
+        }
+        Ok(())
+    }
+
+    pub fn has_history(&self) -> bool {
+        self.history.is_some()
+    }
+
+    pub fn sync(&mut self) -> Result<(), Error> {
+        let changes = self.write_dirty()?;
+        self.save_cache()?;
+        self.update_index()?;
+
+        let message = match changes.as_slice() {
+            [] => return Ok(()),
+            [(_, change)] => change.clone(),
+            changes => {
+                let lines = changes
+                    .iter()
+                    .map(|(_, change)| change.as_str())
+                    .collect::<Vec<_>>();
+                format!("update {} projects\n\n{}", changes.len(), lines.join("\n"))
+            }
+        };
+
+        let paths = changes
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        self.commit(&paths, &message)
+    }
+
+    /// Pick up changes made to the storage by someone else since the projects were
+    /// loaded, e.g. a `todo.txt` edited by hand. Projects without unsaved changes
+    /// are reloaded, the others are reported as conflicts.
+    ///
+    /// Only compares what is there, so it can be called as often as needed,
+    /// e.g. whenever a `Watcher` wakes up.
+    pub fn refresh(&mut self) -> Result<Vec<ChangeEvent>, Error> {
+        let locations = self.storage.list()?;
+        let mut events = Vec::default();
+
+        // Read everything before changing anything, so an error leaves the projects as they were
+        let changed = self
+            .projects
+            .iter()
+            .map(
+                |project| match locations.iter().find(|m| m.name == project.name) {
+                    Some(location) if location.archived == project.in_archive => {
+                        project.changed_on_disk()
+                    }
+                    _ => Ok(false),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut idx = 0;
+        for changed in changed {
+            let project = &mut self.projects[idx];
+            idx += 1;
+
+            let Some(location) = locations.iter().find(|m| m.name == project.name) else {
+                // Created but not written yet
+                if project.modified.is_none() && project.is_loaded() {
+                    continue;
+                }
+                if project.dirty {
+                    events.push(ChangeEvent::Conflict(project.name.clone()));
+                    continue;
+                }
+
+                idx -= 1;
+                let project = self.projects.remove(idx);
+                events.push(ChangeEvent::Removed(project.name));
+                continue;
+            };
+
+            if location.archived != project.in_archive && !project.dirty {
+                project.in_archive = location.archived;
+                project.reload();
+                events.push(ChangeEvent::Reloaded(project.name.clone()));
+                continue;
+            }
+
+            if changed {
+                if project.dirty {
+                    events.push(ChangeEvent::Conflict(project.name.clone()));
+                } else {
+                    project.reload();
+                    events.push(ChangeEvent::Reloaded(project.name.clone()));
+                }
             }
         }
+
+        for location in locations {
+            if !is_name(&location.name) {
+                if !self.invalid.contains(&location.name) {
+                    self.invalid.push(location.name);
+                }
+                continue;
+            }
+            if self.find(&location.name).is_none() {
+                let name = location.name.clone();
+                let cached = self.cache.get(&name);
+                self.open_project(location)?.cached = cached;
+                events.push(ChangeEvent::Added(name));
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Store the todo counts of loaded projects so the next run can list
+    /// projects without reading them. Done by [`sync`](Self::sync) as well.
+    pub fn save_cache(&mut self) -> Result<(), Error> {
+        let mut changed = self
+            .cache
+            .retain(self.projects.iter().map(|m| m.name.as_str()));
+
+        for project in &mut self.projects {
+            if project.is_loaded() && project.modified.is_some() {
+                let summary = project.summary()?;
+                changed |= self.cache.set(&project.name, summary);
+                project.cached = Some(summary);
+            }
+        }
+
+        if changed && let Some(root) = self.storage.root() {
+            self.cache.save(root)?;
+        }
+        Ok(())
+    }
+
+    /// Write every dirty project, returning their paths and a summary of the changes.
+    fn write_dirty(&mut self) -> Result<Vec<(PathBuf, String)>, Error> {
+        let mut changes = Vec::default();
+        for project in &mut self.projects {
+            if project.dirty
+                && let Some(change) = project.write(self.conflict_strategy)?
+            {
+                changes.push((project.path(), change));
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Write pending changes after moving projects around, committing them together.
+    pub(crate) fn sync_moved(
+        &mut self,
+        mut paths: Vec<PathBuf>,
+        message: &str,
+    ) -> Result<(), Error> {
+        paths.extend(self.write_dirty()?.into_iter().map(|(path, _)| path));
+        self.update_index()?;
+        self.commit(&paths, message)
+    }
+
+    fn commit(&self, paths: &[PathBuf], message: &str) -> Result<(), Error> {
+        if let Some(git) = &self.history {
+            let paths = paths.iter().map(|m| m.as_path()).collect::<Vec<_>>();
+            git.commit(&paths, message)?;
+        }
         Ok(())
     }
 
-    pub fn create(&mut self, name: String) -> Result<&mut Project, Error> {
+    fn position(&self, name: &str) -> Result<usize, Error> {
+        self.projects
+            .iter()
+            .position(|m| m.name == name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))
+    }
+
+    /// Rename a project, rewriting `+from` to `+to` in the todos of every project.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        if !is_name(to) {
+            return Err(Error::InvalidName(to.to_string()));
+        }
+        if self.find(to).is_some() {
+            return Err(Error::AlreadyExists(to.to_string()));
+        }
+
+        let idx = self.position(from)?;
+        let old = self.projects[idx].location();
+        let new = Location::new(to, old.archived);
+
+        self.storage.rename(&old, &new)?;
+        self.projects[idx].name = to.to_string();
+        self.projects[idx].rebase()?;
+
+        for project in &mut self.projects {
+            project.retag(from, to)?;
+        }
+
+        if !self.links.paths(from).is_empty() {
+            self.links.rename(from, to);
+            self.links.save(&self.config_dir)?;
+        }
+
+        self.sync_moved(
+            vec![old.path(), new.path()],
+            &format!("rename +{from} to +{to}"),
+        )
+    }
+
+    /// Move a project to the trash directory, returning where it ended up.
+    /// Projects that were never written return `None`.
+    pub fn delete(&mut self, name: &str) -> Result<Option<PathBuf>, Error> {
+        let project = self.projects.remove(self.position(name)?);
+        let trash = self.storage.remove(&project.location())?;
+
+        if self.links.remove(name) {
+            self.links.save(&self.config_dir)?;
+        }
+
+        self.sync_moved(vec![project.path()], &format!("delete +{name}"))?;
+
+        Ok(trash)
+    }
+
+    /// Move the todos and README of `from` into `into` and delete `from`.
+    /// Todos tagged `+from` anywhere are retagged `+into`.
+    pub fn merge(&mut self, from: &str, into: &str) -> Result<(), Error> {
+        if from == into {
+            return Ok(());
+        }
+
+        let target = self.get(into)?;
+        // Load both sides before anything moves
+        target.todos()?;
+        target.metadata()?;
+
+        let source = self.get(from)?;
+        let todos = source.todos()?.clone();
+        let description = source.description()?.trim().to_string();
+        let tags = source.metadata()?.tags.clone();
+
+        let target = self.get_mut(into)?;
+
+        for mut todo in todos.iter().cloned() {
+            retag(&mut todo, from, into);
+            if !target.todos()?.iter().any(|m| *m == todo) {
+                target.todos_mut()?.create_todo(todo);
+            }
+        }
+
+        if !description.is_empty() {
+            let target_description = target.description_mut()?;
+            if !target_description.trim().is_empty() {
+                target_description.push_str("\n\n");
+            }
+            target_description.push_str(&format!("## {from}\n\n{description}\n"));
+        }
+
+        for tag in tags {
+            if !target.metadata()?.tags.contains(&tag) {
+                target.metadata_mut()?.tags.push(tag);
+            }
+        }
+
+        let into_path = target.path();
+
+        for project in self.projects.iter_mut().filter(|m| m.name != from) {
+            project.retag(from, into)?;
+        }
+
+        // The source goes last, so a failed write leaves it in place
+        let mut paths = self
+            .write_dirty()?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        let source = self.projects.remove(self.position(from)?);
+        self.storage.remove(&source.location())?;
+
+        if !self.links.paths(from).is_empty() {
+            self.links.rename(from, into);
+            self.links.save(&self.config_dir)?;
+        }
+
+        paths.extend([source.path(), into_path]);
+        self.sync_moved(paths, &format!("merge +{from} into +{into}"))
+    }
+
+    /// Mark a project archived and move it to the archive directory.
+    pub fn archive(&mut self, name: &str) -> Result<(), Error> {
+        self.set_archived(name, true)
+    }
+
+    /// Move an archived project back and mark it active.
+    pub fn unarchive(&mut self, name: &str) -> Result<(), Error> {
+        self.set_archived(name, false)
+    }
+
+    fn set_archived(&mut self, name: &str, archived: bool) -> Result<(), Error> {
+        let idx = self.position(name)?;
+        let project = &self.projects[idx];
+        let status = project.status()?;
+        if project.in_archive == archived && (status == Status::Archived) == archived {
+            return Ok(());
+        }
+
+        let old = project.location();
+        let new = Location::new(name, archived);
+
+        if old != new {
+            self.storage.rename(&old, &new)?;
+        }
+
+        let project = &mut self.projects[idx];
+        project.in_archive = archived;
+        project.rebase()?;
+        project.set_status(if archived {
+            Status::Archived
+        } else {
+            Status::Active
+        })?;
+
+        let verb = if archived { "archive" } else { "unarchive" };
+        self.sync_moved(vec![old.path(), new.path()], &format!("{verb} +{name}"))
+    }
+
+    /// Commits touching the project, newest first.
+    pub fn history(&self, name: &str) -> Result<Vec<Revision>, Error> {
+        let git = self.history.as_ref().ok_or(Error::HistoryDisabled)?;
+        if !is_name(name) {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        let active = Location::new(name, false).path();
+        let archived = Location::new(name, true).path();
+        git.log(&[&active, &archived])
+    }
+
+    /// The project as it was at revision `rev`.
+    pub fn show(&self, name: &str, rev: &str) -> Result<Project<S>, Error> {
+        let git = self.history.as_ref().ok_or(Error::HistoryDisabled)?;
+        if !is_name(name) {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+
+        let id = git.resolve(rev)?;
+
+        for location in [Location::new(name, false), Location::new(name, true)] {
+            let read = |part: Part| {
+                let path = location.path().join(part.file_name());
+                git.read(&id, &path.to_string_lossy().replace('\\', "/"))
+            };
+            let description = read(Part::Description)?;
+            let todos = read(Part::Todos)?;
+            let metadata = read(Part::Metadata)?;
+
+            if description.is_none() && todos.is_none() && metadata.is_none() {
+                continue;
+            }
+
+            let mut project = Project::new(name.to_string(), self.storage.clone());
+            *project.description_mut()? = description.unwrap_or_default();
+            *project.todos_mut()? = parse_todos(
+                &self.storage.path(&location, Part::Todos),
+                &todos.unwrap_or_default(),
+            )?;
+            *project.metadata_mut()? = Metadata::parse(
+                &self.storage.path(&location, Part::Metadata),
+                &metadata.unwrap_or_default(),
+            )?;
+            project.dirty = false;
+
+            return Ok(project);
+        }
+
+        Err(Error::NotFound(format!("{name}@{rev}")))
+    }
+
+    /// Replace the project with its version at `rev`. The change is written on the next sync.
+    pub fn restore(&mut self, name: &str, rev: &str) -> Result<(), Error> {
+        let mut old = self.show(name, rev)?;
+        let project = self.get_or_create(name)?;
+        // The archived status goes with the project's location, which stays as is
+        let metadata = Metadata {
+            status: project.status()?,
+            ..old.metadata()?.clone()
+        };
+        *project.description_mut()? = std::mem::take(old.description_mut()?);
+        *project.todos_mut()? = std::mem::take(old.todos_mut()?);
+        *project.metadata_mut()? = metadata;
+        Ok(())
+    }
+
+    /// Pick up a project written to the storage behind our back.
+    pub(crate) fn open_project(&mut self, location: Location) -> Result<&mut Project<S>, Error> {
+        let project = Project::open(self.storage.clone(), location)?;
+        self.projects.push(project);
+        Ok(self.projects.last_mut().unwrap())
+    }
+
+    pub fn create(&mut self, name: String) -> Result<&mut Project<S>, Error> {
+        if !is_name(&name) {
+            return Err(Error::InvalidName(name));
+        }
+
         if self.projects.iter().any(|m| m.name == name) {
-            panic!("Project already defined");
+            return Err(Error::AlreadyExists(name));
+        }
+
+        self.projects.push(Project::new(name, self.storage.clone()));
+
+        Ok(self.projects.last_mut().unwrap())
+    }
+
+    pub fn get_or_create(&mut self, name: &str) -> Result<&mut Project<S>, Error> {
+        match self.projects.iter().position(|m| m.name == name) {
+            Some(idx) => Ok(&mut self.projects[idx]),
+            None => self.create(name.to_string()),
         }
+    }
 
-        self.projects.push(Project::new(name.clone()));
+    pub fn get(&self, name: &str) -> Result<&Project<S>, Error> {
+        self.find(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))
+    }
 
-        self.find_mut(&name).ok_or_else(|| todo!())
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Project<S>, Error> {
+        self.find_mut(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))
     }
 
-    pub fn find(&mut self, name: &str) -> Option<&Project> {
+    pub fn find(&self, name: &str) -> Option<&Project<S>> {
         self.projects.iter().find(|m| m.name.as_str() == name)
     }
 
-    pub fn find_mut(&mut self, name: &str) -> Option<&mut Project> {
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Project<S>> {
         self.projects.iter_mut().find(|m| m.name.as_str() == name)
     }
 
-    pub fn iter(&self) -> core::slice::Iter<'_, Project> {
+    pub fn iter(&self) -> core::slice::Iter<'_, Project<S>> {
         self.projects.iter()
     }
 
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Project<S>> {
+        self.projects.iter_mut()
+    }
+
+    /// Store `todo` in its owning project, the first of its `+projects`.
+    pub fn add_todo(&mut self, todo: Todo) -> Result<TodoRef, Error> {
+        let owner = todo.projects.first().cloned().ok_or(Error::NoProject)?;
+        let project = self.get_or_create(&owner)?;
+        let todos = project.todos_mut()?;
+        todos.create_todo(todo);
+
+        Ok(TodoRef {
+            index: todos.len() - 1,
+            project: owner,
+        })
+    }
+
+    pub fn todo(&self, todo: &TodoRef) -> Result<Option<&Todo>, Error> {
+        match self.find(&todo.project) {
+            Some(project) => Ok(project.todos()?.get(todo.index)),
+            None => Ok(None),
+        }
+    }
+
+    /// The todo as stored in its owner. Changes show up in every project listing it.
+    pub fn todo_mut(&mut self, todo: &TodoRef) -> Result<Option<&mut Todo>, Error> {
+        let Some(project) = self.find_mut(&todo.project) else {
+            return Ok(None);
+        };
+        if todo.index >= project.todos()?.len() {
+            return Ok(None);
+        }
+        Ok(project.todos_mut()?.get_mut(todo.index))
+    }
+
+    /// Replace a todo. When its first project changed it moves to that project,
+    /// which shifts the todos after it in the old one.
+    pub fn update_todo(&mut self, todo: &TodoRef, new: Todo) -> Result<TodoRef, Error> {
+        let owner = new.projects.first().ok_or(Error::NoProject)?;
+        if *owner != todo.project {
+            self.remove_todo(todo)?;
+            return self.add_todo(new);
+        }
+
+        match self.todo_mut(todo)? {
+            Some(old) => {
+                *old = new;
+                Ok(todo.clone())
+            }
+            None => self.add_todo(new),
+        }
+    }
+
+    pub fn remove_todo(&mut self, todo: &TodoRef) -> Result<Option<Todo>, Error> {
+        let Some(project) = self.find_mut(&todo.project) else {
+            return Ok(None);
+        };
+        if todo.index >= project.todos()?.len() {
+            return Ok(None);
+        }
+        Ok(project.todos_mut()?.remove(todo.index))
+    }
+
+    /// Todos listed in a project: the ones it owns followed by
+    /// the ones owned elsewhere and tagged with its name.
+    ///
+    /// Finding the tagged ones loads every project. Other projects that can't be
+    /// read are skipped, only the project itself failing is an error.
+    pub fn listed(&self, name: &str) -> Result<Listed<'_>, Error> {
+        let mut listed = Listed::default();
+        if let Some(project) = self.find(name) {
+            listed.todos.extend(refs(project)?);
+        }
+
+        for project in self.projects.iter().filter(|m| m.name != name) {
+            match refs(project) {
+                Ok(refs) => listed
+                    .todos
+                    .extend(refs.filter(|(_, todo)| todo.projects.iter().any(|m| m == name))),
+                Err(err) => listed.skipped.push((project.name.clone(), err)),
+            }
+        }
+
+        Ok(listed)
+    }
+
+    /// Collapse copies of the same todo stored in several projects, as written
+    /// before todos had a single owner. Copies are the same todo when description,
+    /// creation date and projects match. The owner keeps the todo, marked done
+    /// if any copy was. Returns the number of copies removed.
+    pub fn consolidate(&mut self) -> Result<usize, Error> {
+        let mut copies = HashMap::<_, Vec<(usize, usize)>>::new();
+
+        for (project_idx, project) in self.projects.iter().enumerate() {
+            for (todo_idx, todo) in project.todos()?.iter().enumerate() {
+                let locations = copies
+                    .entry((&todo.description, todo.created, &todo.projects))
+                    .or_default();
+                if !locations.iter().any(|(idx, _)| *idx == project_idx) {
+                    locations.push((project_idx, todo_idx));
+                }
+            }
+        }
+
+        let mut updates = Vec::default();
+        let mut removals = Vec::default();
+
+        for ((_, _, tags), locations) in copies {
+            if locations.len() < 2 {
+                continue;
+            }
+
+            let keep = locations
+                .iter()
+                .position(|(idx, _)| Some(&self.projects[*idx].name) == tags.first())
+                .unwrap_or(0);
+
+            let (keep_project, keep_todo) = locations[keep];
+            let mut todo = self.projects[keep_project]
+                .todos()?
+                .get(keep_todo)
+                .cloned()
+                .unwrap();
+
+            for (project_idx, todo_idx) in locations.iter().copied() {
+                let copy = self.projects[project_idx].todos()?.get(todo_idx).unwrap();
+                if copy.done && (!todo.done || copy.completed > todo.completed) {
+                    todo.done = true;
+                    todo.completed = copy.completed;
+                }
+            }
+
+            updates.push((keep_project, keep_todo, todo));
+            removals.extend(
+                locations
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(idx, _)| *idx != keep)
+                    .map(|(_, location)| location),
+            );
+        }
+
+        for (project_idx, todo_idx, todo) in updates {
+            let project = &mut self.projects[project_idx];
+            if project.todos()?.get(todo_idx) != Some(&todo) {
+                *project.todos_mut()?.get_mut(todo_idx).unwrap() = todo;
+            }
+        }
+
+        // Remove from the back so the remaining indices stay valid
+        removals.sort_unstable_by(|a, b| b.cmp(a));
+        for (project_idx, todo_idx) in &removals {
+            self.projects[*project_idx].todos_mut()?.remove(*todo_idx);
+        }
+
+        Ok(removals.len())
+    }
+
+    /// Projects that aren't archived.
+    pub fn active(&self) -> impl Iterator<Item = &Project<S>> {
+        self.projects.iter().filter(|m| !m.is_archived())
+    }
+
+    /// Projects matching `filter`. Projects whose metadata can't be read are
+    /// yielded as errors so the rest can still be listed.
+    pub fn filter<'a>(
+        &'a self,
+        filter: &'a Filter,
+    ) -> impl Iterator<Item = Result<&'a Project<S>, Error>> + 'a {
+        self.projects
+            .iter()
+            .filter_map(|m| match filter.matches(m) {
+                Ok(true) => Some(Ok(m)),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            })
+    }
+
     pub fn len(&self) -> usize {
         self.projects.len()
     }
@@ -188,3 +1202,63 @@ impl Projects {
         self.projects.is_empty()
     }
 }
+
+/// The todos as lines, reusing the line a todo was read from when it didn't change,
+/// so files round trip byte for byte and untouched lines merge cleanly.
+pub(crate) fn render_todos(todos: &Collection, original: &str) -> String {
+    let mut lines = HashMap::<String, Vec<&str>>::new();
+    for line in original.lines().rev() {
+        if let Ok(todo) = parse(line).and_then(Todo::from) {
+            lines.entry(todo.to_string()).or_default().push(line);
+        }
+    }
+
+    let mut out = String::default();
+    for todo in todos.iter() {
+        let rendered = todo.to_string();
+        let line = lines
+            .get_mut(&rendered)
+            .and_then(|m| m.pop())
+            .unwrap_or(&rendered);
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+pub(crate) fn parse_todos(path: &Path, input: &str) -> Result<Collection, Error> {
+    Collection::open_reader(input.as_bytes()).map_err(|err| match err {
+        ReadError::Io(err) => Error::Io(err),
+        ReadError::Parse { line, error } => Error::Parse {
+            path: path.to_path_buf(),
+            line,
+            message: error.to_string(),
+        },
+    })
+}
+
+pub(crate) fn retag(todo: &mut Todo, from: &str, to: &str) {
+    let Some(idx) = todo.projects.iter().position(|m| m == from) else {
+        return;
+    };
+
+    if todo.projects.iter().any(|m| m == to) {
+        todo.projects.remove(idx);
+    } else {
+        todo.projects[idx] = to.to_string();
+    }
+}
+
+fn refs<S: Storage + ?Sized>(
+    project: &Project<S>,
+) -> Result<impl Iterator<Item = (TodoRef, &Todo)>, Error> {
+    Ok(project.todos()?.iter().enumerate().map(|(index, todo)| {
+        (
+            TodoRef {
+                project: project.name.clone(),
+                index,
+            },
+            todo,
+        )
+    }))
+}
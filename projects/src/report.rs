@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, TimeDelta};
+use todotxt::{Clock, Todo};
+
+use crate::{Error, Project, Storage};
+
+const DEFAULT_RECENT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+/// Status document for a single project: its README followed by overdue,
+/// open and recently completed todos.
+pub struct Report<'a> {
+    name: &'a str,
+    description: &'a str,
+    todos: Vec<&'a Todo>,
+    today: NaiveDate,
+    recent_days: i64,
+}
+
+impl<'a> Report<'a> {
+    pub fn new<S: Storage + ?Sized>(
+        project: &'a Project<S>,
+        clock: &impl Clock,
+    ) -> Result<Report<'a>, Error> {
+        Ok(Report {
+            name: project.name(),
+            description: project.description()?,
+            todos: project.todos()?.iter().collect(),
+            today: clock.today(),
+            recent_days: DEFAULT_RECENT_DAYS,
+        })
+    }
+
+    /// How many days back a completed todo counts as recent.
+    pub fn recent_days(mut self, days: i64) -> Self {
+        self.recent_days = days;
+        self
+    }
+
+    /// Report on these todos instead of the ones the project owns,
+    /// e.g. everything [listed](crate::Projects::listed) in it.
+    pub fn todos(mut self, todos: impl IntoIterator<Item = &'a Todo>) -> Self {
+        self.todos = todos.into_iter().collect();
+        self
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Markdown => self.markdown(),
+            Format::Html => self.html(),
+        }
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!("# {}\n\n", escape(self.name)));
+
+        let description = self.description.trim();
+        if !description.is_empty() {
+            out.push_str(description);
+            out.push_str("\n\n");
+        }
+
+        let overdue = self.overdue();
+        if !overdue.is_empty() {
+            out.push_str("## Overdue\n\n");
+            write_list(&mut out, &overdue);
+        }
+
+        out.push_str("## Open\n\n");
+        let open = self.open_by_context();
+        if open.is_empty() {
+            out.push_str("Nothing to do.\n\n");
+        }
+        for (context, todos) in open {
+            match context {
+                Some(context) => out.push_str(&format!("### @{}\n\n", escape(context))),
+                None => out.push_str("### No context\n\n"),
+            }
+            write_list(&mut out, &todos);
+        }
+
+        let completed = self.recently_completed();
+        if !completed.is_empty() {
+            out.push_str("## Recently completed\n\n");
+            write_list(&mut out, &completed);
+        }
+
+        out
+    }
+
+    pub fn html(&self) -> String {
+        let markdown = self.markdown();
+        // Text is kept as written, and HTML in READMEs and todos is shown rather than run
+        let options =
+            pulldown_cmark::Options::all() - pulldown_cmark::Options::ENABLE_SMART_PUNCTUATION;
+        let parser = pulldown_cmark::Parser::new_ext(&markdown, options).map(|event| match event {
+            pulldown_cmark::Event::Text(text)
+            | pulldown_cmark::Event::Html(text)
+            | pulldown_cmark::Event::InlineHtml(text) => {
+                pulldown_cmark::Event::InlineHtml(escape_html(&text).into())
+            }
+            event => event,
+        });
+
+        let mut body = String::new();
+        pulldown_cmark::html::push_html(&mut body, parser);
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(self.name),
+            body
+        )
+    }
+
+    fn overdue(&self) -> Vec<&'a Todo> {
+        let mut todos = self
+            .todos
+            .iter()
+            .copied()
+            .filter(|m| self.is_overdue(m))
+            .collect::<Vec<_>>();
+        todos.sort_by_key(|m| m.due());
+        todos
+    }
+
+    fn open_by_context(&self) -> BTreeMap<Option<&'a str>, Vec<&'a Todo>> {
+        let mut groups: BTreeMap<Option<&str>, Vec<&Todo>> = BTreeMap::default();
+
+        // Overdue todos have their own section
+        for todo in self
+            .todos
+            .iter()
+            .copied()
+            .filter(|m| !m.done && !self.is_overdue(m))
+        {
+            if todo.contexts.is_empty() {
+                groups.entry(None).or_default().push(todo);
+            }
+            for context in &todo.contexts {
+                groups.entry(Some(context.as_str())).or_default().push(todo);
+            }
+        }
+
+        for todos in groups.values_mut() {
+            // Todos without priority sort after every prioritized todo
+            todos.sort_by_key(|m| (m.priority.is_none(), m.priority));
+        }
+
+        groups
+    }
+
+    fn is_overdue(&self, todo: &Todo) -> bool {
+        !todo.done && todo.due().is_some_and(|due| due < self.today)
+    }
+
+    fn recently_completed(&self) -> Vec<&'a Todo> {
+        let since = self.today - TimeDelta::days(self.recent_days);
+        let mut todos = self
+            .todos
+            .iter()
+            .copied()
+            .filter(|m| m.done && m.completed.is_some_and(|date| date >= since))
+            .collect::<Vec<_>>();
+        todos.sort_by_key(|m| core::cmp::Reverse(m.completed));
+        todos
+    }
+}
+
+fn write_list(out: &mut String, todos: &[&Todo]) {
+    for todo in todos {
+        out.push_str("- ");
+        if todo.done {
+            out.push_str("~~");
+        }
+        if let Some(priority) = todo.priority {
+            out.push_str(&format!("**({priority})** "));
+        }
+        out.push_str(&escape(&todo.description));
+        if todo.done {
+            out.push_str("~~");
+        }
+        for project in &todo.projects {
+            out.push_str(&format!(" `+{project}`"));
+        }
+        for context in &todo.contexts {
+            out.push_str(&format!(" `@{context}`"));
+        }
+        if let Some(due) = todo.due() {
+            out.push_str(&format!(" _due {due}_"));
+        }
+        if let Some(completed) = todo.completed {
+            out.push_str(&format!(" _completed {completed}_"));
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+fn escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '~' | '|' | '&'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
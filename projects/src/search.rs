@@ -0,0 +1,363 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Project, Projects, Storage, fs};
+
+const INDEX_FILE: &str = ".index.toml";
+
+/// Characters of context kept around the first match in a snippet.
+const CONTEXT: usize = 40;
+
+// Okapi BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Where in a project a [`Hit`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    Description,
+    /// The todo at this index in the project's todos.
+    Todo(usize),
+}
+
+/// A search result, best first from [`Projects::search`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub project: String,
+    pub field: Field,
+    pub score: f64,
+    /// The matching text on one line, shortened around the first match.
+    pub snippet: String,
+    /// Byte ranges of the matched words in `snippet`.
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// What [`Projects::search`] found.
+#[derive(Debug)]
+pub struct SearchResults {
+    /// Best first.
+    pub hits: Vec<Hit>,
+    /// Projects that couldn't be read and were left out.
+    pub skipped: Vec<(String, Error)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Document {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    todo: Option<usize>,
+    text: String,
+}
+
+impl Document {
+    fn field(&self) -> Field {
+        match self.todo {
+            Some(idx) => Field::Todo(idx),
+            None => Field::Description,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    /// When the todos were last changed on disk as of indexing.
+    modified: Option<DateTime<Utc>>,
+    /// Same for the README.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description_modified: Option<DateTime<Utc>>,
+    documents: Vec<Document>,
+}
+
+/// Project READMEs and todo descriptions by word, kept in `.index.toml`
+/// next to the projects so searching doesn't read every project.
+#[derive(Debug, Default)]
+struct Index {
+    projects: BTreeMap<String, Entry>,
+    /// Term to (project, document, count), rebuilt on load.
+    postings: HashMap<String, Vec<(String, usize, u32)>>,
+    /// Number of terms in each document.
+    lengths: HashMap<(String, usize), usize>,
+}
+
+impl Index {
+    /// A missing or unreadable index is just empty.
+    fn load(dir: &Path) -> Index {
+        let projects = std::fs::read_to_string(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|m| toml::from_str::<BTreeMap<String, Entry>>(&m).ok())
+            .unwrap_or_default();
+
+        let mut index = Index::default();
+        for (name, entry) in projects {
+            index.insert(name, entry);
+        }
+        index
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), Error> {
+        let content = toml::to_string(&self.projects).expect("index serializes to toml");
+        fs::write_atomic(&dir.join(INDEX_FILE), content.as_bytes())?;
+        Ok(())
+    }
+
+    fn insert(&mut self, name: String, entry: Entry) {
+        for (idx, document) in entry.documents.iter().enumerate() {
+            let mut counts = HashMap::<String, u32>::new();
+            for (_, term) in terms(&document.text) {
+                *counts.entry(term).or_default() += 1;
+            }
+
+            self.lengths.insert(
+                (name.clone(), idx),
+                counts.values().map(|&m| m as usize).sum(),
+            );
+            for (term, count) in counts {
+                self.postings
+                    .entry(term)
+                    .or_default()
+                    .push((name.clone(), idx, count));
+            }
+        }
+        self.projects.insert(name, entry);
+    }
+
+    fn remove(&mut self, name: &str) {
+        if self.projects.remove(name).is_none() {
+            return;
+        }
+        self.lengths.retain(|(project, _), _| project != name);
+        self.postings.retain(|_, postings| {
+            postings.retain(|(project, _, _)| project != name);
+            !postings.is_empty()
+        });
+    }
+
+    /// Index the project again if its text changed. Returns true if it did.
+    fn update<S: Storage + ?Sized>(&mut self, project: &Project<S>) -> Result<bool, Error> {
+        let mut documents = vec![Document {
+            todo: None,
+            text: project.description()?.to_string(),
+        }];
+        for (idx, todo) in project.todos()?.iter().enumerate() {
+            documents.push(Document {
+                todo: Some(idx),
+                text: todo.description.clone(),
+            });
+        }
+
+        let entry = Entry {
+            modified: project.modified(),
+            description_modified: project.description_modified(),
+            documents,
+        };
+        if self.projects.get(project.name()) == Some(&entry) {
+            return Ok(false);
+        }
+
+        self.remove(project.name());
+        self.insert(project.name().to_string(), entry);
+        Ok(true)
+    }
+
+    /// Drop projects not in `names`. Returns true if any were dropped.
+    fn retain<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) -> bool {
+        let names = names.into_iter().collect::<Vec<_>>();
+        let gone = self
+            .projects
+            .keys()
+            .filter(|m| !names.contains(&m.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in &gone {
+            self.remove(name);
+        }
+        !gone.is_empty()
+    }
+
+    fn query(&self, query: &str) -> Vec<Hit> {
+        let mut query = terms(query).map(|(_, term)| term).collect::<Vec<_>>();
+        query.sort();
+        query.dedup();
+
+        let count = self.lengths.len() as f64;
+        let average = self.lengths.values().sum::<usize>() as f64 / count.max(1.0);
+
+        let mut scores = HashMap::<(&str, usize), f64>::new();
+        for term in &query {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+
+            let found = postings.len() as f64;
+            let idf = ((count - found + 0.5) / (found + 0.5) + 1.0).ln();
+            for (project, idx, tf) in postings {
+                let length = self.lengths[&(project.clone(), *idx)] as f64;
+                let tf = *tf as f64;
+                *scores.entry((project, *idx)).or_default() +=
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average));
+            }
+        }
+
+        let mut hits = scores
+            .into_iter()
+            .map(|((project, idx), score)| {
+                let document = &self.projects[project].documents[idx];
+                let (snippet, highlights) = snippet(&document.text, &query);
+                Hit {
+                    project: project.to_string(),
+                    field: document.field(),
+                    score,
+                    snippet,
+                    highlights,
+                }
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.project.cmp(&b.project))
+                .then_with(|| a.field.cmp(&b.field))
+        });
+
+        hits
+    }
+}
+
+/// Lowercased words with their byte ranges in `text`.
+fn terms(text: &str) -> impl Iterator<Item = (Range<usize>, String)> + '_ {
+    let mut words = Vec::default();
+    let mut start = None;
+    for (idx, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(idx),
+            (Some(from), false) => {
+                words.push(from..idx);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    words
+        .into_iter()
+        .map(|range| (range.clone(), text[range].to_lowercase()))
+}
+
+/// The line around the first match with every match highlighted.
+fn snippet(text: &str, query: &[String]) -> (String, Vec<Range<usize>>) {
+    let matches = terms(text)
+        .filter(|(_, term)| query.contains(term))
+        .map(|(range, _)| range)
+        .collect::<Vec<_>>();
+    let first = matches.first().map_or(0, |m| m.start);
+
+    let line_start = text[..first].rfind('\n').map_or(0, |m| m + 1);
+    let line_end = text[first..].find('\n').map_or(text.len(), |m| first + m);
+
+    let mut start = text[line_start..first]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT)
+        .map_or(line_start, |(idx, _)| line_start + idx);
+    let mut end = text[first..line_end]
+        .char_indices()
+        .nth(2 * CONTEXT)
+        .map_or(line_end, |(idx, _)| first + idx);
+    // Don't cut words in half
+    if start > line_start {
+        start = text[start..first]
+            .find(char::is_whitespace)
+            .map_or(first, |m| start + m + 1);
+    }
+    if end < line_end {
+        end = text[first..end]
+            .rfind(char::is_whitespace)
+            .map_or(end, |m| first + m);
+    }
+
+    let mut snippet = String::default();
+    if start > line_start {
+        snippet.push_str("...");
+    }
+    let offset = snippet.len();
+    snippet.push_str(&text[start..end]);
+    let highlights = matches
+        .into_iter()
+        .filter(|m| m.start >= start && m.end <= end)
+        .map(|m| m.start - start + offset..m.end - start + offset)
+        .collect();
+    if end < line_end {
+        snippet.push_str("...");
+    }
+
+    (snippet, highlights)
+}
+
+impl<S: Storage + ?Sized> Projects<S> {
+    /// Find projects and todos mentioning any of the words in `query`, best matches first.
+    ///
+    /// Projects changed since they were indexed are indexed again first. Projects
+    /// that can't be read are left out and reported instead of failing the search.
+    pub fn search(&mut self, query: &str) -> Result<SearchResults, Error> {
+        let root = self.storage().root().map(Path::to_path_buf);
+        let mut index = root.as_deref().map(Index::load).unwrap_or_default();
+        let mut skipped = Vec::default();
+
+        let mut changed = index.retain(self.iter().map(|m| m.name()));
+        for project in self.iter() {
+            let stale = index.projects.get(project.name()).is_none_or(|m| {
+                m.modified != project.modified()
+                    || m.description_modified != project.description_modified()
+            });
+            if !project.is_loaded() && !stale {
+                continue;
+            }
+
+            match index.update(project) {
+                Ok(updated) => changed |= updated,
+                Err(err) => {
+                    // Tried again next time
+                    changed |= index.projects.contains_key(project.name());
+                    index.remove(project.name());
+                    skipped.push((project.name().to_string(), err));
+                }
+            }
+        }
+
+        if changed && let Some(root) = &root {
+            index.save(root)?;
+        }
+
+        Ok(SearchResults {
+            hits: index.query(query),
+            skipped,
+        })
+    }
+
+    /// Index loaded projects again, if the workspace has been searched before.
+    /// Done by [`sync`](Self::sync).
+    pub(crate) fn update_index(&self) -> Result<(), Error> {
+        let Some(root) = self.storage().root() else {
+            return Ok(());
+        };
+        if !root.join(INDEX_FILE).exists() {
+            return Ok(());
+        }
+
+        let mut index = Index::load(root);
+        let mut changed = index.retain(self.iter().map(|m| m.name()));
+        for project in self.iter().filter(|m| m.is_loaded()) {
+            changed |= index.update(project)?;
+        }
+
+        if changed {
+            index.save(root)?;
+        }
+        Ok(())
+    }
+}
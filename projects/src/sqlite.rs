@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use todotxt::Todo;
+
+use crate::{Error, Location, Part, Storage, fs::Lock};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS projects (
+    name TEXT PRIMARY KEY,
+    archived INTEGER NOT NULL DEFAULT 0,
+    description TEXT NOT NULL DEFAULT '',
+    metadata TEXT NOT NULL DEFAULT '',
+    done_todos TEXT NOT NULL DEFAULT '',
+    modified TEXT
+);
+
+CREATE TABLE IF NOT EXISTS todos (
+    project TEXT NOT NULL REFERENCES projects (name) ON UPDATE CASCADE ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    line TEXT NOT NULL,
+    done INTEGER,
+    priority TEXT,
+    created TEXT,
+    completed TEXT,
+    due TEXT,
+    description TEXT,
+    PRIMARY KEY (project, position)
+);
+
+CREATE INDEX IF NOT EXISTS todos_due ON todos (due) WHERE due IS NOT NULL;
+CREATE INDEX IF NOT EXISTS todos_completed ON todos (completed) WHERE completed IS NOT NULL;
+";
+
+/// Projects in an SQLite database.
+///
+/// Each todo is a row in `todos` holding the line exactly as written, so files
+/// round trip byte for byte, next to parsed `done`, `priority`, `created`,
+/// `completed`, `due` and `description` columns for querying through
+/// [`connection`](Self::connection). Lines that don't parse leave those empty.
+pub struct SqliteStorage {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl Into<PathBuf>) -> Result<SqliteStorage, Error> {
+        let path = path.into();
+        let connection = Connection::open(&path)?;
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteStorage { path, connection })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    fn exists(&self, project: &Location) -> Result<bool, Error> {
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT 1 FROM projects WHERE name = ?1 AND archived = ?2",
+                params![project.name, project.archived],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn list(&self) -> Result<Vec<Location>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT name, archived FROM projects ORDER BY name")?;
+        let rows = statement.query_map([], |row| {
+            Ok(Location::new(row.get::<_, String>(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error> {
+        if !self.exists(project)? {
+            return Ok(String::default());
+        }
+
+        let content = match part {
+            Part::Description | Part::Done | Part::Metadata => self.connection.query_row(
+                &format!("SELECT {} FROM projects WHERE name = ?1", column(part)),
+                params![project.name],
+                |row| row.get(0),
+            )?,
+            Part::Todos => {
+                let mut statement = self
+                    .connection
+                    .prepare("SELECT line FROM todos WHERE project = ?1 ORDER BY position")?;
+                let lines =
+                    statement.query_map(params![project.name], |row| row.get::<_, String>(0))?;
+                lines.collect::<Result<String, _>>()?
+            }
+        };
+
+        Ok(content)
+    }
+
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error> {
+        let tx = self.connection.unchecked_transaction()?;
+
+        tx.execute(
+            "INSERT OR IGNORE INTO projects (name, archived) VALUES (?1, ?2)",
+            params![project.name, project.archived],
+        )?;
+
+        match part {
+            Part::Description | Part::Done | Part::Metadata => {
+                tx.execute(
+                    &format!("UPDATE projects SET {} = ?2 WHERE name = ?1", column(part)),
+                    params![project.name, content],
+                )?;
+            }
+            Part::Todos => {
+                tx.execute(
+                    "DELETE FROM todos WHERE project = ?1",
+                    params![project.name],
+                )?;
+
+                let mut insert = tx.prepare(
+                    "INSERT INTO todos (project, position, line, done, priority, created, completed, due, description)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )?;
+
+                // Lines keep their terminator so a missing final newline survives
+                for (position, line) in content.split_inclusive('\n').enumerate() {
+                    let todo = todotxt::parser::parse(line.trim_end_matches(['\r', '\n']))
+                        .ok()
+                        .and_then(|m| Todo::from(m).ok());
+
+                    insert.execute(params![
+                        project.name,
+                        position as i64,
+                        line,
+                        todo.as_ref().map(|m| m.done),
+                        todo.as_ref().and_then(|m| m.priority).map(String::from),
+                        todo.as_ref().and_then(|m| m.created).map(|m| m.to_string()),
+                        todo.as_ref()
+                            .and_then(|m| m.completed)
+                            .map(|m| m.to_string()),
+                        todo.as_ref().and_then(|m| m.due()).map(|m| m.to_string()),
+                        todo.as_ref().map(|m| m.description.as_str()),
+                    ])?;
+                }
+
+                tx.execute(
+                    "UPDATE projects SET modified = ?2 WHERE name = ?1",
+                    params![project.name, Utc::now().to_rfc3339()],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn modified(&self, project: &Location) -> Option<DateTime<Utc>> {
+        let modified = self
+            .connection
+            .query_row(
+                "SELECT modified FROM projects WHERE name = ?1 AND archived = ?2",
+                params![project.name, project.archived],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .ok()??;
+        Some(DateTime::parse_from_rfc3339(&modified).ok()?.to_utc())
+    }
+
+    fn lock(&self, _project: &Location) -> Result<Lock, Error> {
+        Ok(Lock::file(&self.path)?)
+    }
+
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        let taken = self
+            .connection
+            .query_row(
+                "SELECT 1 FROM projects WHERE name = ?1",
+                params![to.name],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if from.name != to.name && taken {
+            return Err(Error::AlreadyExists(to.name.clone()));
+        }
+
+        self.connection.execute(
+            "UPDATE projects SET name = ?3, archived = ?4 WHERE name = ?1 AND archived = ?2",
+            params![from.name, from.archived, to.name, to.archived],
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error> {
+        self.connection.execute(
+            "DELETE FROM projects WHERE name = ?1 AND archived = ?2",
+            params![project.name, project.archived],
+        )?;
+        Ok(None)
+    }
+
+    fn path(&self, _project: &Location, _part: Part) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+fn column(part: Part) -> &'static str {
+    match part {
+        Part::Description => "description",
+        Part::Metadata => "metadata",
+        Part::Done => "done_todos",
+        Part::Todos => unreachable!("todos are kept in their own table"),
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(value: rusqlite::Error) -> Self {
+        Error::Database(value.to_string())
+    }
+}
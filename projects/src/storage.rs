@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, fs::Lock};
+
+/// The files making up a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    /// `README.md`
+    Description,
+    /// `todo.txt`
+    Todos,
+    /// `done.txt`, completed todos moved out of `todo.txt`.
+    Done,
+    /// `project.toml`, see [`Metadata`](crate::Metadata).
+    Metadata,
+}
+
+impl Part {
+    pub const ALL: [Part; 4] = [Part::Description, Part::Todos, Part::Done, Part::Metadata];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Part::Description => "README.md",
+            Part::Todos => "todo.txt",
+            Part::Done => "done.txt",
+            Part::Metadata => "project.toml",
+        }
+    }
+}
+
+/// Where a project is kept in a [`Storage`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Location {
+    pub name: String,
+    /// Moved out of the way by [`Projects::archive`](crate::Projects::archive).
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl Location {
+    pub fn new(name: impl Into<String>, archived: bool) -> Location {
+        Location {
+            name: name.into(),
+            archived,
+        }
+    }
+
+    /// Path relative to a directory store.
+    pub fn path(&self) -> PathBuf {
+        if self.archived {
+            Path::new(ARCHIVE_DIR).join(&self.name)
+        } else {
+            PathBuf::from(&self.name)
+        }
+    }
+}
+
+pub(crate) const ARCHIVE_DIR: &str = ".archive";
+
+/// Backing store for [`Projects`](crate::Projects).
+///
+/// Projects are read and written as the raw text of their [`Part`]s so
+/// every store round trips files byte for byte. Parts that don't exist read as empty.
+pub trait Storage {
+    /// Every project in the store.
+    fn list(&self) -> Result<Vec<Location>, Error>;
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error>;
+
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error>;
+
+    /// When the todos of a project last changed, if the store knows.
+    fn modified(&self, project: &Location) -> Option<DateTime<Utc>>;
+
+    /// When any part of a project last changed, if the store knows.
+    /// Only the todos unless the store says otherwise.
+    fn part_modified(&self, project: &Location, part: Part) -> Option<DateTime<Utc>> {
+        match part {
+            Part::Todos => self.modified(project),
+            _ => None,
+        }
+    }
+
+    /// Keep other processes from writing the project until the lock is dropped.
+    fn lock(&self, project: &Location) -> Result<Lock, Error>;
+
+    /// Move a project, failing with [`Error::AlreadyExists`] if `to` is taken.
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error>;
+
+    /// Delete a project. Stores that keep deleted projects around return where.
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error>;
+
+    /// Where a part is kept, for error messages.
+    fn path(&self, project: &Location, part: Part) -> PathBuf {
+        project.path().join(part.file_name())
+    }
+
+    /// Whether every project lives in one file together with todos that belong
+    /// to none, like a plain `todo.txt`. Deleting every project doesn't empty it.
+    fn is_shared(&self) -> bool {
+        false
+    }
+
+    /// Directory holding the store, for the cache and history.
+    fn root(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// What [`copy`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Copied {
+    pub projects: usize,
+    /// Parts left out because the target can't store them, like READMEs in a
+    /// plain `todo.txt`.
+    pub skipped: Vec<(Location, Part)>,
+}
+
+/// Copy every project from one store to another.
+///
+/// Parts are copied as raw text, so nothing is lost between stores that keep
+/// every part. Fails with [`Error::AlreadyExists`] before writing anything if
+/// `to` already has one of the projects. If writing fails, the projects
+/// copied so far are removed again.
+pub fn copy(from: &dyn Storage, to: &dyn Storage) -> Result<Copied, Error> {
+    let projects = from.list()?;
+    let existing = to.list()?;
+
+    if let Some(project) = projects
+        .iter()
+        .find(|m| existing.iter().any(|e| e.name == m.name))
+    {
+        return Err(Error::AlreadyExists(project.name.clone()));
+    }
+
+    // Read everything up front, so a bad source doesn't leave half a copy
+    let mut contents = Vec::default();
+    for project in &projects {
+        for part in Part::ALL {
+            let content = from.read(project, part)?;
+            if !content.is_empty() {
+                contents.push((project, part, content));
+            }
+        }
+    }
+
+    let mut copied = Copied {
+        projects: projects.len(),
+        skipped: Vec::default(),
+    };
+    let mut written = Vec::<&Location>::default();
+
+    for (project, part, content) in contents {
+        let result = to
+            .lock(project)
+            .and_then(|_lock| to.write(project, part, &content));
+        match result {
+            Ok(()) => {
+                if !written.contains(&project) {
+                    written.push(project);
+                }
+            }
+            Err(Error::Unsupported(_)) => copied.skipped.push((project.clone(), part)),
+            Err(err) => {
+                for project in written {
+                    let _ = to.remove(project);
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(copied)
+}
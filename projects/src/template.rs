@@ -0,0 +1,118 @@
+use std::{io, path::Path};
+
+use todotxt::{Clock, Collection};
+
+use crate::{Error, Metadata, Part, Project, Projects, Storage, fs, projects::parse_todos};
+
+const TEMPLATES_DIR: &str = "templates";
+
+/// A project skeleton in `templates/<name>` in the config directory, holding any of
+/// `README.md`, `todo.txt` and `project.toml`:
+///
+/// ```text
+/// templates/release/README.md   # Release {{name}}
+/// templates/release/todo.txt    Tag the release +{{name}} due:{{today}}
+/// ```
+///
+/// `{{name}}` is replaced with the project name and `{{today}}` with today's date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    name: String,
+    description: String,
+    todos: String,
+    metadata: String,
+}
+
+impl Template {
+    pub fn load(config_dir: &Path, name: &str) -> Result<Template, Error> {
+        let dir = config_dir.join(TEMPLATES_DIR).join(name);
+        if !todotxt::parser::is_name(name) || !dir.is_dir() {
+            return Err(Error::TemplateNotFound(name.to_string()));
+        }
+
+        Ok(Template {
+            name: name.to_string(),
+            description: fs::read_optional(&dir.join(Part::Description.file_name()))?,
+            todos: fs::read_optional(&dir.join(Part::Todos.file_name()))?,
+            metadata: fs::read_optional(&dir.join(Part::Metadata.file_name()))?,
+        })
+    }
+
+    /// Names of the templates in the config directory.
+    pub fn list(config_dir: &Path) -> Result<Vec<String>, Error> {
+        let entries = match std::fs::read_dir(config_dir.join(TEMPLATES_DIR)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut names = Vec::default();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && todotxt::parser::is_name(name)
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// README, todos and metadata for a project called `project`.
+    /// Todos are made to belong to it and dated today.
+    fn render(
+        &self,
+        project: &str,
+        config_dir: &Path,
+        clock: &impl Clock,
+    ) -> Result<(String, Collection, Metadata), Error> {
+        let today = clock.today().to_string();
+        let render = |input: &str| {
+            input
+                .replace("{{name}}", project)
+                .replace("{{today}}", &today)
+        };
+
+        let dir = config_dir.join(TEMPLATES_DIR).join(&self.name);
+        let mut todos = parse_todos(&dir.join(Part::Todos.file_name()), &render(&self.todos))?;
+        let metadata = Metadata::parse(
+            &dir.join(Part::Metadata.file_name()),
+            &render(&self.metadata),
+        )?;
+
+        for todo in todos.iter_mut() {
+            todo.projects.retain(|m| m != project);
+            todo.projects.insert(0, project.to_string());
+            todo.created.get_or_insert(clock.today());
+        }
+
+        Ok((render(&self.description), todos, metadata))
+    }
+}
+
+impl<S: Storage + ?Sized> Projects<S> {
+    /// Create a project from the template of that name in the config directory.
+    pub fn create_from_template(
+        &mut self,
+        name: String,
+        template: &str,
+        clock: &impl Clock,
+    ) -> Result<&mut Project<S>, Error> {
+        let template = Template::load(self.config_dir(), template)?;
+        let (description, todos, metadata) = template.render(&name, self.config_dir(), clock)?;
+
+        let project = self.create(name)?;
+        *project.description_mut()? = description;
+        *project.todos_mut()? = todos;
+        *project.metadata_mut()? = metadata;
+
+        Ok(project)
+    }
+}
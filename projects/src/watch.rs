@@ -0,0 +1,104 @@
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+
+use crate::{ChangeEvent, Error, Projects, Storage, storage::ARCHIVE_DIR};
+
+/// Wakes up when the files of a projects directory change, so long running
+/// programs can [refresh](Projects::refresh) instead of holding stale projects.
+///
+/// Storages without a data directory are polled every `interval` instead.
+/// Directory storages are polled as well, in case an event gets lost.
+pub struct Watcher {
+    watcher: Option<RecommendedWatcher>,
+    events: Receiver<()>,
+    interval: Duration,
+}
+
+impl Watcher {
+    pub fn new<S: Storage + ?Sized>(
+        projects: &Projects<S>,
+        interval: Duration,
+    ) -> Result<Watcher, Error> {
+        let (sender, events) = mpsc::channel();
+
+        let watcher = match projects.data_dir() {
+            Some(root) => {
+                let root_path = root.to_path_buf();
+                let mut watcher =
+                    notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                        if let Ok(event) = event
+                            && event.paths.iter().any(|m| is_relevant(&root_path, m))
+                        {
+                            let _ = sender.send(());
+                        }
+                    })?;
+                watcher.watch(root, RecursiveMode::Recursive)?;
+                Some(watcher)
+            }
+            None => None,
+        };
+
+        Ok(Watcher {
+            watcher,
+            events,
+            interval,
+        })
+    }
+
+    /// Block until something changed or the interval passed, then refresh.
+    /// Returns what changed, often nothing after a timeout.
+    pub fn wait<S: Storage + ?Sized>(
+        &self,
+        projects: &mut Projects<S>,
+    ) -> Result<Vec<ChangeEvent>, Error> {
+        match self.events.recv_timeout(self.interval) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+            // The watcher is gone, fall back to polling
+            Err(RecvTimeoutError::Disconnected) => std::thread::sleep(self.interval),
+        }
+        self.refresh(projects)
+    }
+
+    /// Refresh if something changed, without blocking. Always refreshes
+    /// storages that can only be polled.
+    pub fn poll<S: Storage + ?Sized>(
+        &self,
+        projects: &mut Projects<S>,
+    ) -> Result<Vec<ChangeEvent>, Error> {
+        if self.events.try_recv().is_err() && self.watcher.is_some() {
+            return Ok(Vec::default());
+        }
+        self.refresh(projects)
+    }
+
+    fn refresh<S: Storage + ?Sized>(
+        &self,
+        projects: &mut Projects<S>,
+    ) -> Result<Vec<ChangeEvent>, Error> {
+        // Editors tend to write a file in several steps
+        while self.events.try_recv().is_ok() {}
+        projects.refresh()
+    }
+}
+
+/// Skips our own lock, temporary and cache files, the trash and git.
+fn is_relevant(root: &Path, path: &Path) -> bool {
+    let Ok(path) = path.strip_prefix(root) else {
+        return false;
+    };
+    path.components().all(|m| {
+        let name = m.as_os_str().to_string_lossy();
+        !name.starts_with('.') || name == ARCHIVE_DIR
+    })
+}
+
+impl From<notify::Error> for Error {
+    fn from(value: notify::Error) -> Self {
+        Error::Watch(value.to_string())
+    }
+}
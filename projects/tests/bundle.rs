@@ -0,0 +1,74 @@
+use projects::{Error, FileStorage, ImportMode, Location, MemoryStorage, Part, Projects, Storage};
+
+fn site() -> Location {
+    Location::new("site", false)
+}
+
+fn memory(todos: &str, done: &str) -> Projects<MemoryStorage> {
+    let storage = MemoryStorage::new();
+    storage.write(&site(), Part::Todos, todos).unwrap();
+    if !done.is_empty() {
+        storage.write(&site(), Part::Done, done).unwrap();
+    }
+    Projects::with_storage(storage, "/nonexistent").unwrap()
+}
+
+fn bundle(projects: &Projects<MemoryStorage>) -> Vec<u8> {
+    let mut bundle = Vec::default();
+    projects.export(&mut bundle).unwrap();
+    bundle
+}
+
+#[test]
+fn done_todos_round_trip() {
+    let from = memory(
+        "Fix header +site\n",
+        "x 2026-10-01 2026-09-01 Set up +site\n",
+    );
+
+    let mut to = Projects::with_storage(MemoryStorage::new(), "/nonexistent").unwrap();
+    assert_eq!(to.import(&bundle(&from)[..], ImportMode::Merge).unwrap(), 1);
+
+    assert_eq!(
+        to.storage().read(&site(), Part::Done).unwrap(),
+        "x 2026-10-01 2026-09-01 Set up +site\n"
+    );
+}
+
+#[test]
+fn done_todos_merge_into_existing_project() {
+    let from = memory(
+        "Fix header +site\n",
+        "x 2026-10-01 2026-09-01 Set up +site\nx 2026-10-02 2026-09-02 Buy domain +site\n",
+    );
+
+    let mut to = memory(
+        "Add footer +site\n",
+        "x 2026-10-01 2026-09-01 Set up +site\n",
+    );
+    to.import(&bundle(&from)[..], ImportMode::Merge).unwrap();
+
+    assert_eq!(
+        to.storage().read(&site(), Part::Done).unwrap(),
+        "x 2026-10-01 2026-09-01 Set up +site\nx 2026-10-02 2026-09-02 Buy domain +site\n"
+    );
+}
+
+#[test]
+fn replace_is_refused_for_a_shared_file() {
+    let from = memory("Fix header +site\n", "");
+
+    let dir = std::env::temp_dir().join(format!("projects-bundle-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("todo.txt");
+    let content = "Call mom\nLaunch +web\n";
+    std::fs::write(&file, content).unwrap();
+
+    let mut to = Projects::with_storage(FileStorage::open(&file), dir.join(".config")).unwrap();
+    let result = to.import(&bundle(&from)[..], ImportMode::Replace);
+
+    assert!(matches!(result, Err(Error::Unsupported(_))));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), content);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,136 @@
+use projects::{MemoryStorage, Projects, checklist::Checklist};
+use todotxt::{FixedClock, Todo, parser::parse};
+
+const README: &str = "\
+# Launch
+
+- [ ] read https://example.com/guide
+- [x] write intro
+";
+
+fn clock() -> FixedClock {
+    FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap())
+}
+
+/// A project with the checklist synced once.
+fn projects() -> Projects<MemoryStorage> {
+    let mut projects = Projects::with_storage(MemoryStorage::new(), "/nonexistent").unwrap();
+    let project = projects.create("site".to_string()).unwrap();
+    *project.description_mut().unwrap() = README.to_string();
+
+    let added = Checklist {
+        added: 2,
+        ..Default::default()
+    };
+    assert_eq!(project.sync_checklist(&clock()).unwrap(), added);
+    projects
+}
+
+fn sync(projects: &mut Projects<MemoryStorage>) -> Checklist {
+    projects
+        .get_mut("site")
+        .unwrap()
+        .sync_checklist(&clock())
+        .unwrap()
+}
+
+fn readme(projects: &Projects<MemoryStorage>) -> &str {
+    projects.get("site").unwrap().description().unwrap()
+}
+
+fn edit_readme(projects: &mut Projects<MemoryStorage>, from: &str, to: &str) {
+    let readme = projects.get_mut("site").unwrap().description_mut().unwrap();
+    assert!(readme.contains(from));
+    *readme = readme.replace(from, to);
+}
+
+fn todo(projects: &mut Projects<MemoryStorage>, idx: usize) -> &mut Todo {
+    projects
+        .get_mut("site")
+        .unwrap()
+        .todos_mut()
+        .unwrap()
+        .get_mut(idx)
+        .unwrap()
+}
+
+#[test]
+fn items_become_todos() {
+    let mut projects = projects();
+
+    assert_eq!(
+        readme(&projects),
+        "\
+# Launch
+
+- [ ] read https://example.com/guide <!-- todo:1 -->
+- [x] write intro <!-- todo:2 x -->
+"
+    );
+
+    let todos = projects.get("site").unwrap().todos().unwrap();
+    for (line, todo) in todos.to_string().lines().zip(todos.iter()) {
+        assert_eq!(&Todo::from(parse(line).unwrap()).unwrap(), todo);
+    }
+    assert_eq!(todos.get(0).unwrap().projects, ["site"]);
+    assert!(todos.get(1).unwrap().done);
+
+    assert_eq!(sync(&mut projects), Checklist::default());
+}
+
+#[test]
+fn todo_changes_reach_the_readme() {
+    let mut projects = projects();
+
+    todo(&mut projects, 0).complete(&clock());
+    todo(&mut projects, 1).reopen();
+    let synced = Checklist {
+        checked: 1,
+        unchecked: 1,
+        ..Default::default()
+    };
+    assert_eq!(sync(&mut projects), synced);
+    assert!(readme(&projects).contains("- [x] read https://example.com/guide <!-- todo:1 x -->"));
+    assert!(readme(&projects).contains("- [ ] write intro <!-- todo:2 -->"));
+
+    // The reopened todo stays open
+    assert_eq!(sync(&mut projects), Checklist::default());
+    assert!(!todo(&mut projects, 1).done);
+}
+
+#[test]
+fn readme_changes_reach_the_todos() {
+    let mut projects = projects();
+
+    edit_readme(&mut projects, "- [ ] read", "- [x] read");
+    edit_readme(&mut projects, "- [x] write", "- [ ] write");
+    let synced = Checklist {
+        completed: 1,
+        reopened: 1,
+        ..Default::default()
+    };
+    assert_eq!(sync(&mut projects), synced);
+    assert!(todo(&mut projects, 0).done);
+    assert!(!todo(&mut projects, 1).done);
+    assert!(readme(&projects).contains("- [ ] write intro <!-- todo:2 -->"));
+
+    // The unchecked item stays unchecked
+    assert_eq!(sync(&mut projects), Checklist::default());
+    assert!(readme(&projects).contains("- [ ] write intro <!-- todo:2 -->"));
+}
+
+#[test]
+fn changes_on_both_sides_agree() {
+    let mut projects = projects();
+
+    edit_readme(&mut projects, "- [ ] read", "- [x] read");
+    todo(&mut projects, 0).complete(&clock());
+    edit_readme(&mut projects, "- [x] write", "- [ ] write");
+    todo(&mut projects, 1).reopen();
+
+    assert_eq!(sync(&mut projects), Checklist::default());
+    assert!(todo(&mut projects, 0).done);
+    assert!(!todo(&mut projects, 1).done);
+    assert!(readme(&projects).contains("- [x] read https://example.com/guide <!-- todo:1 x -->"));
+    assert!(readme(&projects).contains("- [ ] write intro <!-- todo:2 -->"));
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use projects::{OpenOptions, PROFILE_ENV, Projects, ROOT_ENV};
+
+fn open(options: &OpenOptions) -> (PathBuf, Option<String>) {
+    let projects: Projects = options.open().unwrap();
+    (
+        projects.data_dir().unwrap().to_path_buf(),
+        projects.profile().map(str::to_string),
+    )
+}
+
+fn set(name: &str, value: Option<&Path>) {
+    // Only this test touches the environment of this test binary
+    unsafe {
+        match value {
+            Some(value) => std::env::set_var(name, value),
+            None => std::env::remove_var(name),
+        }
+    }
+}
+
+#[test]
+fn data_directory_precedence() {
+    let root = std::env::temp_dir().join(format!("projects-config-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let config_dir = root.join("config");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        format!(
+            "default_profile = \"home\"\n\n[profiles.work]\npath = {:?}\n",
+            root.join("work")
+        ),
+    )
+    .unwrap();
+
+    set("HOME", Some(&root));
+    set("XDG_DATA_HOME", Some(&root.join("data")));
+    set(ROOT_ENV, Some(&root.join("env")));
+    set(PROFILE_ENV, None);
+    let data_dir = directories::ProjectDirs::from("com", "Softshag", "Projects")
+        .unwrap()
+        .data_local_dir()
+        .to_path_buf();
+
+    let mut options = OpenOptions::new();
+    options.config_dir(&config_dir);
+
+    // An explicit root wins over everything
+    let explicit = options
+        .clone()
+        .root(root.join("explicit"))
+        .profile("work")
+        .clone();
+    assert_eq!(open(&explicit), (root.join("explicit"), None));
+
+    // Then an explicit profile, with its configured path
+    let profile = options.clone().profile("work").clone();
+    assert_eq!(open(&profile), (root.join("work"), Some("work".into())));
+
+    // Then the root variable
+    set(PROFILE_ENV, Some(Path::new("work")));
+    assert_eq!(open(&options), (root.join("env"), None));
+
+    // Then the profile variable
+    set(ROOT_ENV, None);
+    assert_eq!(open(&options), (root.join("work"), Some("work".into())));
+
+    // Then the default profile, which has no path
+    set(PROFILE_ENV, None);
+    let home = data_dir.join(".profiles").join("home");
+    assert_eq!(open(&options), (home, Some("home".into())));
+
+    // And the platform data directory, where profiles aren't projects
+    std::fs::write(config_dir.join("config.toml"), "").unwrap();
+    assert_eq!(open(&options), (data_dir, None));
+    assert!(options.open().unwrap().is_empty());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use projects::{
+    DirStorage, Projects,
+    harvest::{FILE_KEY, Harvest, LINE_KEY},
+};
+use todotxt::{FixedClock, Value};
+
+fn clock() -> FixedClock {
+    FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap())
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("projects-harvest-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("data").join("site")).unwrap();
+    std::fs::write(dir.join("data").join("site").join("todo.txt"), "").unwrap();
+    dir
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join(file), content).unwrap();
+}
+
+fn open(root: &Path) -> Projects<DirStorage> {
+    let storage = DirStorage::open(root.join("data")).unwrap();
+    Projects::with_storage(storage, root.join("config")).unwrap()
+}
+
+#[test]
+fn comments_are_reconciled() {
+    let root = temp_dir("reconcile");
+    let src = root.join("src");
+    write(&src, "main.rs", "fn main() {}\n// TODO: handle errors\n");
+
+    let mut projects = open(&root);
+    projects.link("site", &src).unwrap();
+    let harvest = |projects: &mut Projects<DirStorage>| projects.harvest("site", &clock()).unwrap();
+    let todo = |projects: &Projects<DirStorage>| {
+        projects
+            .get("site")
+            .unwrap()
+            .todos()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .clone()
+    };
+
+    let added = Harvest {
+        added: 1,
+        ..Default::default()
+    };
+    assert_eq!(harvest(&mut projects), added);
+    assert_eq!(harvest(&mut projects), Harvest::default());
+
+    write(
+        &src,
+        "main.rs",
+        "\n\nfn main() {}\n// TODO: handle errors\n",
+    );
+    let moved = Harvest {
+        moved: 1,
+        ..Default::default()
+    };
+    assert_eq!(harvest(&mut projects), moved);
+    assert_eq!(todo(&projects).get(LINE_KEY), Some(&Value::Int(4)));
+
+    write(&src, "main.rs", "fn main() {}\n");
+    let completed = Harvest {
+        completed: 1,
+        ..Default::default()
+    };
+    assert_eq!(harvest(&mut projects), completed);
+    assert!(todo(&projects).done);
+
+    write(&src, "main.rs", "fn main() {}\n// TODO: handle errors\n");
+    let reopened = Harvest {
+        moved: 1,
+        reopened: 1,
+        ..Default::default()
+    };
+    assert_eq!(harvest(&mut projects), reopened);
+    assert!(!todo(&projects).done);
+    assert_eq!(projects.get("site").unwrap().todos().unwrap().len(), 1);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn urls_are_written_so_the_project_loads() {
+    let root = temp_dir("url");
+    let src = root.join("src");
+    write(
+        &src,
+        "main.rs",
+        "fn main() {}\n// TODO: see http://example.com for details\n",
+    );
+
+    let mut projects = open(&root);
+    projects.link("site", &src).unwrap();
+    projects.harvest("site", &clock()).unwrap();
+    projects.sync().unwrap();
+
+    let projects = open(&root);
+    let todos = projects.get("site").unwrap().todos().unwrap();
+    let todo = todos.get(0).unwrap();
+    assert_eq!(todo.description, "see http：//example.com for details");
+    assert_eq!(todo.projects, ["site"]);
+    assert_eq!(todo.get(LINE_KEY), Some(&Value::Int(2)));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn same_path_in_two_roots() {
+    let root = temp_dir("roots");
+    for dir in ["a", "b"] {
+        write(&root.join(dir), "main.rs", "// FIXME: remove unwraps\n");
+    }
+
+    let mut projects = open(&root);
+    projects.link("site", &root.join("a")).unwrap();
+    projects.link("site", &root.join("b")).unwrap();
+
+    let added = Harvest {
+        added: 2,
+        ..Default::default()
+    };
+    assert_eq!(projects.harvest("site", &clock()).unwrap(), added);
+    assert_eq!(
+        projects.harvest("site", &clock()).unwrap(),
+        Harvest::default()
+    );
+
+    let files = projects
+        .get("site")
+        .unwrap()
+        .todos()
+        .unwrap()
+        .iter()
+        .map(|m| m.get(FILE_KEY).cloned())
+        .collect::<Vec<_>>();
+    let file = |dir: &str| {
+        let path = root.join(dir).canonicalize().unwrap().join("main.rs");
+        Some(Value::String(path.to_string_lossy().replace('\\', "/")))
+    };
+    assert_eq!(files, [file("a"), file("b")]);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
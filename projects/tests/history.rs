@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use projects::{DirStorage, Projects, Status};
+use todotxt::{FixedClock, Todo, parser::parse};
+
+const TODOS: &str = "\
+(A) 2026-10-02 Ship it +site @work id:8
+Write notes @home +site
+2026-10-01 Fix header +site due:2026-11-01
+";
+
+fn open(name: &str) -> (Projects<DirStorage>, PathBuf) {
+    let root = std::env::temp_dir().join(format!("projects-history-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("site")).unwrap();
+    std::fs::write(root.join("site").join("todo.txt"), TODOS).unwrap();
+
+    let storage = DirStorage::open(&root).unwrap();
+    let mut projects = Projects::with_storage(storage, root.join(".config")).unwrap();
+    projects.enable_history().unwrap();
+    (projects, root)
+}
+
+fn messages(projects: &Projects<DirStorage>) -> Vec<String> {
+    projects
+        .history("site")
+        .unwrap()
+        .into_iter()
+        .map(|m| m.message)
+        .collect()
+}
+
+#[test]
+fn moved_todos_are_not_committed() {
+    let (mut projects, root) = open("moved");
+    let before = messages(&projects);
+
+    let todos = projects.get_mut("site").unwrap().todos_mut().unwrap();
+    let todo = todos.remove(0).unwrap();
+    todos.create_todo(todo);
+    projects.sync().unwrap();
+
+    assert_eq!(messages(&projects), before);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn moved_and_completed_todo_is_a_completion() {
+    let clock = FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+    let (mut projects, root) = open("completed");
+
+    let todos = projects.get_mut("site").unwrap().todos_mut().unwrap();
+    let mut todo = todos.remove(0).unwrap();
+    todo.complete(&clock);
+    todos.create_todo(todo);
+    todos.create_todo(Todo::from(parse("Add footer +site").unwrap()).unwrap());
+    projects.sync().unwrap();
+
+    assert_eq!(
+        messages(&projects).first().map(String::as_str),
+        Some("add 1 todo, complete 1 todo in +site")
+    );
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn metadata_changes_are_not_readme_changes() {
+    let (mut projects, root) = open("metadata");
+
+    let site = projects.get_mut("site").unwrap();
+    site.set_status(Status::Paused).unwrap();
+    projects.sync().unwrap();
+    assert_eq!(
+        messages(&projects).first().map(String::as_str),
+        Some("update metadata in +site")
+    );
+
+    let site = projects.get_mut("site").unwrap();
+    *site.description_mut().unwrap() = "Company website".to_string();
+    site.set_status(Status::Active).unwrap();
+    projects.sync().unwrap();
+    assert_eq!(
+        messages(&projects).first().map(String::as_str),
+        Some("update README, update metadata in +site")
+    );
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,29 @@
+use projects::{DirStorage, Projects};
+
+#[test]
+fn invalid_directories_are_skipped() {
+    let root = std::env::temp_dir().join(format!("projects-load-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("site")).unwrap();
+    std::fs::create_dir_all(root.join("Old stuff")).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let name = std::ffi::OsStr::from_bytes(b"caf\xe9");
+        std::fs::create_dir_all(root.join(name)).unwrap();
+    }
+
+    let storage = DirStorage::open(&root).unwrap();
+    let mut projects = Projects::with_storage(storage, root.join(".config")).unwrap();
+
+    assert!(projects.get("site").is_ok());
+    assert!(projects.invalid().contains(&"Old stuff".to_string()));
+    #[cfg(unix)]
+    assert_eq!(projects.invalid().len(), 2);
+
+    std::fs::create_dir_all(root.join("blog")).unwrap();
+    projects.refresh().unwrap();
+    assert!(projects.get("blog").is_ok());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,169 @@
+use projects::{ConflictStrategy, Error, Location, MemoryStorage, Part, Projects, Storage};
+use todotxt::{FixedClock, Todo, parser::parse};
+
+const TODOS: &str = "\
+2026-10-01 Fix header +site file:\"a.rs\" line:3 id:7 due:2026-11-01
+(A) 2026-10-02 Ship it +site @work id:8 owner:sara due:2026-11-02
+Write notes @home +site
+";
+
+fn open(todos: &str) -> Projects<MemoryStorage> {
+    let storage = MemoryStorage::new();
+    storage.write(&site(), Part::Todos, todos).unwrap();
+
+    let mut projects = Projects::with_storage(storage, "/nonexistent").unwrap();
+    projects.set_conflict_strategy(ConflictStrategy::Merge);
+    projects
+}
+
+fn site() -> Location {
+    Location::new("site", false)
+}
+
+fn todo(line: &str) -> Todo {
+    Todo::from(parse(line).unwrap()).unwrap()
+}
+
+fn stored(projects: &Projects<MemoryStorage>) -> String {
+    projects.storage().read(&site(), Part::Todos).unwrap()
+}
+
+#[test]
+fn untouched_lines_round_trip() {
+    for _ in 0..5 {
+        let mut projects = open(TODOS);
+        projects.get_mut("site").unwrap().todos_mut().unwrap();
+        projects.sync().unwrap();
+
+        assert_eq!(stored(&projects), TODOS);
+    }
+}
+
+#[test]
+fn changed_todo_keeps_key_order() {
+    let mut projects = open(TODOS);
+    let todos = projects.get_mut("site").unwrap().todos_mut().unwrap();
+    todos.get_mut(0).unwrap().priority = Some('B');
+    projects.sync().unwrap();
+
+    assert_eq!(
+        stored(&projects).lines().next().unwrap(),
+        "(B) 2026-10-01 Fix header +site file:\"a.rs\" line:3 id:7 due:2026-11-01"
+    );
+}
+
+#[test]
+fn external_completion_merges_with_added_todo() {
+    let clock = FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+
+    for _ in 0..5 {
+        let mut projects = open(TODOS);
+        projects
+            .get_mut("site")
+            .unwrap()
+            .todos_mut()
+            .unwrap()
+            .create_todo(todo("Add footer +site id:9 kind:task"));
+
+        // Completed in an editor meanwhile
+        let completed = TODOS.replace("(A) 2026-10-02", "x (A) 2026-10-20 2026-10-02");
+        projects
+            .storage()
+            .write(&site(), Part::Todos, &completed)
+            .unwrap();
+
+        projects.sync().unwrap();
+
+        let merged = stored(&projects);
+        assert_eq!(merged.lines().count(), 4, "{merged}");
+        assert!(merged.contains("x (A) 2026-10-20 2026-10-02 Ship it +site @work id:8 owner:sara"));
+        assert!(
+            !merged
+                .lines()
+                .any(|m| m.starts_with("(A) 2026-10-02 Ship it"))
+        );
+        assert!(merged.contains("Add footer +site id:9 kind:task\n"));
+
+        let todos = projects.get("site").unwrap().todos().unwrap();
+        let shipped = todos.iter().find(|m| m.description == "Ship it").unwrap();
+        assert!(shipped.done);
+        assert_eq!(shipped.completed, Some(clock.0));
+    }
+}
+
+#[test]
+fn external_edit_merges_with_local_completion() {
+    let clock = FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+
+    let mut projects = open(TODOS);
+    projects
+        .get_mut("site")
+        .unwrap()
+        .todos_mut()
+        .unwrap()
+        .get_mut(0)
+        .unwrap()
+        .complete(&clock);
+
+    let edited = TODOS.replace("Write notes @home", "Write release notes @home");
+    projects
+        .storage()
+        .write(&site(), Part::Todos, &edited)
+        .unwrap();
+
+    projects.sync().unwrap();
+
+    assert_eq!(
+        stored(&projects),
+        "\
+X 2026-10-20 2026-10-01 Fix header +site file:\"a.rs\" line:3 id:7 due:2026-11-01
+(A) 2026-10-02 Ship it +site @work id:8 owner:sara due:2026-11-02
+Write release notes @home +site
+"
+    );
+}
+
+#[test]
+fn todo_changed_on_both_sides_conflicts() {
+    let mut projects = open(TODOS);
+    projects
+        .get_mut("site")
+        .unwrap()
+        .todos_mut()
+        .unwrap()
+        .get_mut(2)
+        .unwrap()
+        .priority = Some('B');
+
+    let edited = TODOS.replace("Write notes @home", "Write release notes @home");
+    projects
+        .storage()
+        .write(&site(), Part::Todos, &edited)
+        .unwrap();
+
+    assert!(matches!(projects.sync(), Err(Error::Conflict(name)) if name == "site"));
+    assert_eq!(stored(&projects), edited);
+}
+
+#[test]
+fn same_change_on_both_sides_merges() {
+    let mut projects = open(TODOS);
+    let notes = projects
+        .get_mut("site")
+        .unwrap()
+        .todos_mut()
+        .unwrap()
+        .get_mut(2)
+        .unwrap();
+    notes.priority = Some('B');
+    let line = notes.to_string();
+
+    let edited = TODOS.replace("Write notes @home +site", &line);
+    projects
+        .storage()
+        .write(&site(), Part::Todos, &edited)
+        .unwrap();
+
+    projects.sync().unwrap();
+    assert_eq!(stored(&projects), edited);
+}
@@ -0,0 +1,168 @@
+use std::{cell::Cell, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use projects::{Error, Location, Lock, MemoryStorage, Part, Projects, Status, Storage};
+use todotxt::{Todo, parser::parse};
+
+/// Memory storage whose writes fail on demand.
+#[derive(Default)]
+struct Flaky {
+    inner: MemoryStorage,
+    fail: Cell<bool>,
+}
+
+impl Storage for Flaky {
+    fn list(&self) -> Result<Vec<Location>, Error> {
+        self.inner.list()
+    }
+
+    fn read(&self, project: &Location, part: Part) -> Result<String, Error> {
+        self.inner.read(project, part)
+    }
+
+    fn write(&self, project: &Location, part: Part, content: &str) -> Result<(), Error> {
+        if self.fail.get() {
+            return Err(Error::Io(std::io::Error::other("disk full")));
+        }
+        self.inner.write(project, part, content)
+    }
+
+    fn modified(&self, project: &Location) -> Option<DateTime<Utc>> {
+        self.inner.modified(project)
+    }
+
+    fn lock(&self, project: &Location) -> Result<Lock, Error> {
+        self.inner.lock(project)
+    }
+
+    fn rename(&self, from: &Location, to: &Location) -> Result<(), Error> {
+        self.inner.rename(from, to)
+    }
+
+    fn remove(&self, project: &Location) -> Result<Option<PathBuf>, Error> {
+        self.inner.remove(project)
+    }
+}
+
+fn projects<S: Storage>(storage: S) -> Projects<S> {
+    let mut projects = Projects::with_storage(storage, "/nonexistent").unwrap();
+    for line in [
+        "Fix header +site",
+        "Write post +blog +site",
+        "Add comments +blog",
+    ] {
+        projects
+            .add_todo(Todo::from(parse(line).unwrap()).unwrap())
+            .unwrap();
+    }
+    *projects.get_mut("blog").unwrap().description_mut().unwrap() = "Posts".to_string();
+    projects.sync().unwrap();
+    projects
+}
+
+fn lines<S: Storage + ?Sized>(projects: &Projects<S>, name: &str) -> Vec<String> {
+    projects
+        .get(name)
+        .unwrap()
+        .todos()
+        .unwrap()
+        .iter()
+        .map(|m| m.to_string())
+        .collect()
+}
+
+fn stored<S: Storage>(projects: &Projects<S>) -> Vec<Location> {
+    projects.storage().list().unwrap()
+}
+
+#[test]
+fn rename_retags_todos() {
+    let mut projects = projects(MemoryStorage::new());
+    projects.rename("site", "web").unwrap();
+
+    assert!(projects.get("site").is_err());
+    assert_eq!(lines(&projects, "web"), ["Fix header +web"]);
+    assert_eq!(
+        lines(&projects, "blog"),
+        ["Write post +blog +web", "Add comments +blog"]
+    );
+    assert_eq!(
+        stored(&projects),
+        [Location::new("blog", false), Location::new("web", false)]
+    );
+}
+
+#[test]
+fn delete_removes_the_project() {
+    let mut projects = projects(MemoryStorage::new());
+    assert_eq!(projects.delete("blog").unwrap(), None);
+
+    assert!(projects.get("blog").is_err());
+    assert_eq!(stored(&projects), [Location::new("site", false)]);
+}
+
+#[test]
+fn merge_moves_todos_and_readme() {
+    let mut projects = projects(MemoryStorage::new());
+    projects.merge("blog", "site").unwrap();
+
+    assert!(projects.get("blog").is_err());
+    assert_eq!(stored(&projects), [Location::new("site", false)]);
+    assert_eq!(
+        lines(&projects, "site"),
+        ["Fix header +site", "Write post +site", "Add comments +site"]
+    );
+
+    let site = Location::new("site", false);
+    assert_eq!(
+        projects.storage().read(&site, Part::Description).unwrap(),
+        "## blog\n\nPosts\n"
+    );
+    assert!(
+        projects
+            .storage()
+            .read(&site, Part::Todos)
+            .unwrap()
+            .contains("Add comments +site")
+    );
+}
+
+#[test]
+fn failed_merge_keeps_the_source() {
+    let mut projects = projects(Flaky::default());
+    projects.storage().fail.set(true);
+
+    assert!(projects.merge("blog", "site").is_err());
+
+    let blog = Location::new("blog", false);
+    assert!(stored(&projects).contains(&blog));
+    assert!(
+        projects
+            .storage()
+            .read(&blog, Part::Todos)
+            .unwrap()
+            .contains("Add comments +blog")
+    );
+    assert!(projects.get("blog").is_ok());
+}
+
+#[test]
+fn archive_and_unarchive() {
+    let mut projects = projects(MemoryStorage::new());
+
+    projects.archive("blog").unwrap();
+    let blog = projects.get("blog").unwrap();
+    assert!(blog.is_archived());
+    assert_eq!(blog.status().unwrap(), Status::Archived);
+    assert!(stored(&projects).contains(&Location::new("blog", true)));
+
+    projects.unarchive("blog").unwrap();
+    let blog = projects.get("blog").unwrap();
+    assert!(!blog.is_archived());
+    assert_eq!(blog.status().unwrap(), Status::Active);
+    assert_eq!(
+        lines(&projects, "blog"),
+        ["Write post +blog +site", "Add comments +blog"]
+    );
+    assert!(stored(&projects).contains(&Location::new("blog", false)));
+}
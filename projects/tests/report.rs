@@ -0,0 +1,56 @@
+use projects::{
+    MemoryStorage, Projects,
+    report::{Format, Report},
+};
+use todotxt::{FixedClock, Todo, parser::parse};
+
+fn clock() -> FixedClock {
+    FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap())
+}
+
+fn projects(todos: &[&str], readme: &str) -> Projects<MemoryStorage> {
+    let mut projects = Projects::with_storage(MemoryStorage::new(), "/nonexistent").unwrap();
+    for line in todos {
+        projects
+            .add_todo(Todo::from(parse(line).unwrap()).unwrap())
+            .unwrap();
+    }
+    *projects.get_mut("site").unwrap().description_mut().unwrap() = readme.to_string();
+    projects
+}
+
+#[test]
+fn overdue_todos_are_not_open() {
+    let projects = projects(
+        &[
+            "Fix header +site due:2026-10-01",
+            "Add footer +site due:2026-11-01",
+        ],
+        "",
+    );
+    let report = Report::new(projects.get("site").unwrap(), &clock())
+        .unwrap()
+        .render(Format::Markdown);
+
+    let (overdue, open) = report.split_once("## Open").unwrap();
+    assert!(overdue.contains("Fix header"));
+    assert!(!open.contains("Fix header"));
+    assert!(open.contains("Add footer"));
+}
+
+#[test]
+fn html_is_escaped() {
+    let projects = projects(
+        &["Check <b>bold</b> & \"quotes\" +site"],
+        "# Site\n\n<script>alert(1)</script>\n\nSee <img src=x onerror=alert(1)> & more.\n",
+    );
+    let html = Report::new(projects.get("site").unwrap(), &clock())
+        .unwrap()
+        .render(Format::Html);
+
+    assert!(!html.contains("<script>"));
+    assert!(!html.contains("<img"));
+    assert!(!html.contains("<b>"));
+    assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    assert!(html.contains("Check &lt;b&gt;bold&lt;/b&gt; &amp; &quot;quotes&quot;"));
+}
@@ -0,0 +1,63 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use projects::{DirStorage, Field, Projects};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("projects-search-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(root: &Path, project: &str, file: &str, content: &[u8]) {
+    std::fs::create_dir_all(root.join(project)).unwrap();
+    std::fs::write(root.join(project).join(file), content).unwrap();
+}
+
+fn open(root: &Path) -> Projects<DirStorage> {
+    Projects::with_storage(DirStorage::open(root).unwrap(), root.join(".config")).unwrap()
+}
+
+#[test]
+fn readme_edits_are_reindexed() {
+    let root = temp_dir("readme");
+    write(&root, "site", "todo.txt", b"Fix header +site\n");
+    write(&root, "site", "README.md", b"# Plans for the launch\n");
+
+    assert_eq!(open(&root).search("launch").unwrap().hits.len(), 1);
+
+    write(&root, "site", "README.md", b"# Plans for the migration\n");
+    // Make sure the change is visible whatever the file system's time resolution
+    std::fs::File::options()
+        .write(true)
+        .open(root.join("site").join("README.md"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+
+    let results = open(&root).search("migration").unwrap();
+    assert_eq!(results.hits.len(), 1);
+    assert_eq!(results.hits[0].field, Field::Description);
+    assert!(open(&root).search("launch").unwrap().hits.is_empty());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn unreadable_projects_are_skipped() {
+    let root = temp_dir("unreadable");
+    write(&root, "site", "todo.txt", b"Fix header +site\n");
+    write(&root, "blog", "todo.txt", b"Fix header \xff+blog\n");
+
+    let results = open(&root).search("header").unwrap();
+
+    assert_eq!(results.hits.len(), 1);
+    assert_eq!(results.hits[0].project, "site");
+    assert_eq!(results.skipped.len(), 1);
+    assert_eq!(results.skipped[0].0, "blog");
+
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,173 @@
+use projects::{
+    Copied, DirStorage, Error, FileStorage, Location, MemoryStorage, Part, Storage, copy,
+};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("projects-storage-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// What a store keeps besides the todos of active projects.
+struct Supports {
+    parts: bool,
+    archive: bool,
+}
+
+const ALL: Supports = Supports {
+    parts: true,
+    archive: true,
+};
+
+/// The behavior every [`Storage`] shares.
+fn conformance(storage: &dyn Storage, supports: Supports) {
+    let site = Location::new("site", false);
+    let web = Location::new("web", false);
+
+    assert_eq!(storage.list().unwrap(), []);
+    assert_eq!(storage.read(&site, Part::Todos).unwrap(), "");
+    assert_eq!(storage.modified(&site), None);
+
+    // Read and write
+    storage
+        .write(&site, Part::Todos, "Fix header +site\nAdd footer +site\n")
+        .unwrap();
+    assert_eq!(
+        storage.read(&site, Part::Todos).unwrap(),
+        "Fix header +site\nAdd footer +site\n"
+    );
+    assert!(storage.modified(&site).is_some());
+
+    if supports.parts {
+        storage.write(&site, Part::Description, "# Site\n").unwrap();
+        storage
+            .write(&site, Part::Done, "x 2026-10-01 Set up +site\n")
+            .unwrap();
+        storage
+            .write(&site, Part::Metadata, "status = \"active\"\n")
+            .unwrap();
+        assert_eq!(storage.read(&site, Part::Description).unwrap(), "# Site\n");
+        assert_eq!(
+            storage.read(&site, Part::Done).unwrap(),
+            "x 2026-10-01 Set up +site\n"
+        );
+    } else {
+        assert!(matches!(
+            storage.write(&site, Part::Description, "# Site\n"),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    storage
+        .write(&site, Part::Todos, "Fix header +site\n")
+        .unwrap();
+    assert_eq!(
+        storage.read(&site, Part::Todos).unwrap(),
+        "Fix header +site\n"
+    );
+
+    // List
+    storage.write(&web, Part::Todos, "Launch +web\n").unwrap();
+    let mut list = storage.list().unwrap();
+    list.sort();
+    assert_eq!(list, [site.clone(), web.clone()]);
+
+    // Rename
+    assert!(matches!(
+        storage.rename(&site, &web),
+        Err(Error::AlreadyExists(name)) if name == "web"
+    ));
+    let blog = Location::new("blog", false);
+    storage.rename(&site, &blog).unwrap();
+    assert_eq!(storage.read(&site, Part::Todos).unwrap(), "");
+    assert!(
+        storage
+            .read(&blog, Part::Todos)
+            .unwrap()
+            .starts_with("Fix header +")
+    );
+    if supports.parts {
+        assert_eq!(storage.read(&blog, Part::Description).unwrap(), "# Site\n");
+        assert_eq!(
+            storage.read(&blog, Part::Done).unwrap(),
+            "x 2026-10-01 Set up +site\n"
+        );
+    }
+
+    // Archive
+    let archived = Location::new("blog", true);
+    if supports.archive {
+        storage.rename(&blog, &archived).unwrap();
+        assert!(storage.list().unwrap().contains(&archived));
+        storage.rename(&archived, &blog).unwrap();
+    } else {
+        assert!(storage.rename(&blog, &archived).is_err());
+    }
+
+    // Remove
+    storage.remove(&blog).unwrap();
+    assert_eq!(storage.read(&blog, Part::Todos).unwrap(), "");
+    assert_eq!(storage.read(&web, Part::Todos).unwrap(), "Launch +web\n");
+    assert_eq!(storage.list().unwrap(), [web]);
+}
+
+#[test]
+fn memory_storage() {
+    conformance(&MemoryStorage::new(), ALL);
+}
+
+#[test]
+fn dir_storage() {
+    let dir = temp_dir("dir");
+    conformance(&DirStorage::open(&dir).unwrap(), ALL);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn file_storage() {
+    let dir = temp_dir("file");
+    let storage = FileStorage::open(dir.join("todo.txt"));
+    conformance(
+        &storage,
+        Supports {
+            parts: false,
+            archive: false,
+        },
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage() {
+    let dir = temp_dir("sqlite");
+    conformance(
+        &projects::SqliteStorage::open(dir.join("todo.db")).unwrap(),
+        ALL,
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn copy_skips_parts_the_target_cant_store() {
+    let site = Location::new("site", false);
+    let from = MemoryStorage::new();
+    from.write(&site, Part::Todos, "Fix header +site\n")
+        .unwrap();
+    from.write(&site, Part::Description, "# Site\n").unwrap();
+
+    let dir = temp_dir("copy");
+    let to = FileStorage::open(dir.join("todo.txt"));
+    let copied = copy(&from, &to).unwrap();
+
+    assert_eq!(
+        copied,
+        Copied {
+            projects: 1,
+            skipped: vec![(site.clone(), Part::Description)],
+        }
+    );
+    assert_eq!(to.read(&site, Part::Todos).unwrap(), "Fix header +site\n");
+    std::fs::remove_dir_all(dir).unwrap();
+}
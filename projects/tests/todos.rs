@@ -0,0 +1,73 @@
+use projects::{DirStorage, MemoryStorage, Projects, TodoRef};
+use todotxt::{Todo, parser::parse};
+
+fn todo(line: &str) -> Todo {
+    Todo::from(parse(line).unwrap()).unwrap()
+}
+
+#[test]
+fn update_moves_todo_to_its_first_project() {
+    let mut projects = Projects::with_storage(MemoryStorage::new(), "/nonexistent").unwrap();
+    projects.add_todo(todo("Fix header +site")).unwrap();
+    let footer = projects.add_todo(todo("Add footer +site")).unwrap();
+
+    let moved = projects
+        .update_todo(&footer, todo("Add footer +blog +site"))
+        .unwrap();
+
+    assert_eq!(
+        moved,
+        TodoRef {
+            project: "blog".to_string(),
+            index: 0
+        }
+    );
+    assert_eq!(projects.get("site").unwrap().todos().unwrap().len(), 1);
+    let listed = projects.listed("site").unwrap().todos;
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[1].0, moved);
+}
+
+#[test]
+fn update_in_place_keeps_position() {
+    let mut projects = Projects::with_storage(MemoryStorage::new(), "/nonexistent").unwrap();
+    let header = projects.add_todo(todo("Fix header +site")).unwrap();
+    projects.add_todo(todo("Add footer +site")).unwrap();
+
+    let updated = projects
+        .update_todo(&header, todo("(A) Fix header +site"))
+        .unwrap();
+
+    assert_eq!(updated, header);
+    assert_eq!(projects.todo(&header).unwrap().unwrap().priority, Some('A'));
+}
+
+#[test]
+fn corrupt_projects_are_skipped_when_listing() {
+    let root = std::env::temp_dir().join(format!("projects-todos-{}-corrupt", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (project, todos) in [
+        ("site", "Fix header +site\n"),
+        ("blog", "Write post key:\n"),
+    ] {
+        std::fs::create_dir_all(root.join(project)).unwrap();
+        std::fs::write(root.join(project).join("todo.txt"), todos).unwrap();
+    }
+
+    let projects =
+        Projects::with_storage(DirStorage::open(&root).unwrap(), root.join(".config")).unwrap();
+
+    let listed = projects.listed("site").unwrap();
+    assert_eq!(listed.todos.len(), 1);
+    assert_eq!(
+        listed
+            .skipped
+            .iter()
+            .map(|(m, _)| m.as_str())
+            .collect::<Vec<_>>(),
+        ["blog"]
+    );
+    assert!(projects.listed("blog").is_err());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -1,196 +1,453 @@
 use crossterm::{
     ExecutableCommand, QueueableCommand, cursor,
-    event::{Event, KeyCode, KeyModifiers, read},
+    event::{Event, KeyCode, KeyEvent, KeyModifiers, read},
     style::{self, Stylize},
     terminal::{self, ClearType, disable_raw_mode, enable_raw_mode},
 };
+use projects::TodoRef;
 use std::io::{self, Write};
-use todotxt::Collection;
+use todotxt::{Clock, Todo, parser::parse};
 
-pub fn run(collection: &mut Collection) -> color_eyre::Result<bool> {
-    Editor::new(5, collection)?.run()
+use crate::render_todo;
+
+/// A todo being edited, with the todo it came from unless it was added.
+pub struct Entry {
+    pub origin: Option<TodoRef>,
+    pub todo: Todo,
+}
+
+/// Edit the todos listed in `project` in place. Returns true if they should be saved.
+pub fn run<C: Clock>(
+    entries: &mut Vec<Entry>,
+    project: &str,
+    clock: &C,
+) -> color_eyre::Result<bool> {
+    let (_, rows) = terminal::size()?;
+    let height = (entries.len() as u16)
+        .clamp(5, 15)
+        .min(rows.saturating_sub(3).max(1));
+    Editor::new(height, entries, project, clock)?.run()
 }
 
-struct Editor<'a> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Search,
+    Add,
+    /// Editing the todo at this index in the entries.
+    Edit(usize),
+}
+
+struct Editor<'a, C> {
     window_height: u16,
     buffer: String,
     w: io::Stdout,
     top: u16,
-    collection: &'a mut Collection,
+    entries: &'a mut Vec<Entry>,
+    project: &'a str,
+    clock: &'a C,
+    mode: Mode,
+    /// Only todos containing every word of this are shown.
+    filter: String,
+    /// Indices into the entries of the todos shown.
+    visible: Vec<usize>,
+    /// Index into `visible` of the selected todo.
+    selected: usize,
+    /// Index into `visible` of the first row.
+    scroll: usize,
+    modified: bool,
+    /// Quit was pressed once with unsaved changes.
+    discard: bool,
+    /// Shown instead of the help until the next key.
+    message: Option<String>,
 }
 
-impl<'a> Editor<'a> {
-    fn new(window_height: u16, collection: &'a mut Collection) -> color_eyre::Result<Editor<'a>> {
+impl<'a, C: Clock> Editor<'a, C> {
+    fn new(
+        window_height: u16,
+        entries: &'a mut Vec<Entry>,
+        project: &'a str,
+        clock: &'a C,
+    ) -> color_eyre::Result<Editor<'a, C>> {
         for _ in 0..(window_height + 2) {
             println!();
         }
 
         let (_, current_row) = cursor::position()?;
-        let start = current_row - (window_height + 2);
+        let start = current_row.saturating_sub(window_height + 2);
 
-        Ok(Editor {
+        let mut editor = Editor {
             window_height,
             buffer: String::new(),
             w: io::stdout(),
             top: start,
-            collection,
-        })
+            entries,
+            project,
+            clock,
+            mode: Mode::Normal,
+            filter: String::new(),
+            visible: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            modified: false,
+            discard: false,
+            message: None,
+        };
+        editor.update_visible();
+
+        Ok(editor)
     }
 
     fn run(&mut self) -> color_eyre::Result<bool> {
         enable_raw_mode()?;
         self.w.execute(cursor::Hide)?;
         let ret = self.run_inner();
-        self.w.execute(cursor::Show)?;
+        self.w
+            .queue(cursor::MoveTo(0, self.top + self.window_height + 2))?
+            .execute(cursor::Show)?;
         disable_raw_mode()?;
+        println!();
         ret
     }
 
     fn run_inner(&mut self) -> color_eyre::Result<bool> {
-        self.set_input("")?;
-        self.render_help()?;
-        self.render_list(0, 0)?;
+        self.render()?;
 
-        self.w.queue(cursor::MoveTo(1, self.top))?;
+        loop {
+            let Event::Key(key) = read()? else {
+                continue;
+            };
 
-        self.w.flush()?;
+            self.message = None;
+            let done = match self.mode {
+                Mode::Normal => self.normal(key),
+                Mode::Search => {
+                    self.search(key);
+                    None
+                }
+                Mode::Add | Mode::Edit(_) => {
+                    self.input(key);
+                    None
+                }
+            };
+
+            if let Some(save) = done {
+                return Ok(save);
+            }
 
-        let mut current_row = 0u16;
-        let mut pointer_idx = 0usize;
-
-        let save = loop {
-            let event = read()?;
-
-            match event {
-                Event::Key(key) => {
-                    //
-                    match key.code {
-                        KeyCode::Esc | KeyCode::Char('q') => {
-                            break false;
-                        }
-                        KeyCode::Up => {
-                            if current_row == 0 {
-                                if pointer_idx > 0 {
-                                    pointer_idx -= 1;
-                                }
-                            } else {
-                                current_row -= 1;
-                            }
-
-                            self.render_list(pointer_idx, current_row)?;
-                        }
-                        KeyCode::Down => {
-                            if current_row == self.window_height - 1 {
-                                if (pointer_idx as isize)
-                                    < (self.collection.len() as isize - self.window_height as isize)
-                                {
-                                    pointer_idx += 1;
-                                }
-                            } else {
-                                current_row += 1;
-                            }
-
-                            self.render_list(pointer_idx, current_row)?;
-                        }
-                        KeyCode::Char('d') => {
-                            self.collection.remove(pointer_idx + current_row as usize);
-                            self.render_list(pointer_idx, current_row)?;
-                        }
-                        KeyCode::Char('c') => {
-                            let todo = self
-                                .collection
-                                .get_mut(pointer_idx + current_row as usize)
-                                .unwrap();
-                            if todo.done {
-                                todo.completed = None;
-                                todo.done = false;
-                            } else {
-                                todo.completed = Some(chrono::Local::now().date_naive());
-                                todo.done = true;
-                            }
-                            self.render_list(pointer_idx, current_row)?;
-                        }
-                        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            break true;
-                        }
-
-                        _ => {}
+            self.render()?;
+        }
+    }
+
+    /// Returns whether to save once the editor should close.
+    fn normal(&mut self, key: KeyEvent) -> Option<bool> {
+        let discard = std::mem::take(&mut self.discard);
+
+        match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(true);
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(false);
+            }
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.update_visible();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if !self.modified || discard {
+                    return Some(false);
+                }
+                self.discard = true;
+                self.message = Some("Unsaved changes, press q again to discard".to_string());
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::PageUp => {
+                self.select(self.selected.saturating_sub(self.window_height as usize))
+            }
+            KeyCode::PageDown => self.select(self.selected + self.window_height as usize),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('c') | KeyCode::Char('x') => {
+                if let Some(idx) = self.current() {
+                    let todo = &mut self.entries[idx].todo;
+                    if todo.done {
+                        todo.reopen();
+                    } else {
+                        todo.complete(self.clock);
                     }
+                    self.modified = true;
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(idx) = self.current() {
+                    self.entries.remove(idx);
+                    self.modified = true;
+                    self.update_visible();
+                }
+            }
+            KeyCode::Char('/') => {
+                self.buffer = self.filter.clone();
+                self.mode = Mode::Search;
+            }
+            KeyCode::Char('a') => {
+                self.buffer.clear();
+                self.mode = Mode::Add;
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(idx) = self.current() {
+                    self.buffer = self.entries[idx].todo.to_string();
+                    self.mode = Mode::Edit(idx);
                 }
-                _ => {}
             }
+            _ => {}
+        }
 
-            self.w.queue(cursor::MoveTo(1, self.top))?.flush()?;
-        };
+        None
+    }
+
+    /// Filters the list while typing.
+    fn search(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.mode = Mode::Normal,
+            KeyCode::Esc => {
+                self.buffer.clear();
+                self.mode = Mode::Normal;
+            }
+            _ => self.edit_buffer(key),
+        }
+
+        self.filter = self.buffer.clone();
+        self.update_visible();
+    }
+
+    fn input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.buffer.clear();
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                let todo = match self.parse() {
+                    Ok(todo) => todo,
+                    Err(err) => {
+                        self.message = Some(err);
+                        return;
+                    }
+                };
+
+                match self.mode {
+                    Mode::Edit(idx) => self.entries[idx].todo = todo,
+                    _ => {
+                        self.entries.push(Entry { origin: None, todo });
+                        self.filter.clear();
+                    }
+                }
+
+                self.modified = true;
+                self.buffer.clear();
+                self.update_visible();
+                if self.mode == Mode::Add {
+                    self.select(usize::MAX);
+                }
+                self.mode = Mode::Normal;
+            }
+            _ => self.edit_buffer(key),
+        }
+    }
 
-        Ok(save)
+    fn edit_buffer(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Backspace => {
+                self.buffer.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.buffer.clear();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.buffer.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// The todo in the input line. New todos belong to the project and are dated today.
+    fn parse(&self) -> Result<Todo, String> {
+        let input = self.buffer.trim();
+        if input.is_empty() {
+            return Err("Empty todo".to_string());
+        }
+
+        let mut todo = parse(input)
+            .map_err(|err| err.to_string())
+            .and_then(|m| Todo::from(m).map_err(|err| err.to_string()))?;
+
+        if self.mode == Mode::Add {
+            todo.created.get_or_insert(self.clock.today());
+        }
+        if todo.projects.is_empty() {
+            todo.projects.push(self.project.to_string());
+        }
+
+        Ok(todo)
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.visible.get(self.selected).copied()
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.visible.len().saturating_sub(1));
+
+        let height = self.window_height as usize;
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + height {
+            self.scroll = self.selected + 1 - height;
+        }
+    }
+
+    fn update_visible(&mut self) {
+        let words = self
+            .filter
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        self.visible = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                let text = entry.todo.to_string().to_lowercase();
+                words.iter().all(|word| text.contains(word))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        self.select(self.selected);
+    }
+
+    fn render(&mut self) -> color_eyre::Result<()> {
+        self.render_input()?;
+        self.render_list()?;
+        self.render_help()?;
+        self.w.flush()?;
+        Ok(())
+    }
+
+    fn render_input(&mut self) -> color_eyre::Result<()> {
+        self.w
+            .queue(cursor::MoveTo(0, self.top))?
+            .queue(terminal::Clear(ClearType::CurrentLine))?;
+
+        match self.mode {
+            Mode::Normal if self.filter.is_empty() => {
+                self.w.queue(style::PrintStyledContent(
+                    format!("{} todos", self.project).bold(),
+                ))?;
+                if self.modified {
+                    self.w
+                        .queue(style::PrintStyledContent(" (modified)".dark_grey()))?;
+                }
+            }
+            Mode::Normal | Mode::Search => {
+                self.w
+                    .queue(style::PrintStyledContent("/ ".dark_grey()))?
+                    .queue(style::Print(&self.filter))?;
+            }
+            Mode::Add | Mode::Edit(_) => {
+                // Validated as you type
+                let prompt = match self.parse() {
+                    Ok(_) => "> ".green(),
+                    Err(_) => "> ".red(),
+                };
+                self.w
+                    .queue(style::PrintStyledContent(prompt))?
+                    .queue(style::Print(&self.buffer))?;
+            }
+        }
+
+        if self.mode != Mode::Normal {
+            self.w.queue(style::PrintStyledContent(
+                "_".attribute(style::Attribute::SlowBlink),
+            ))?;
+        }
+
+        Ok(())
     }
 
     fn render_help(&mut self) -> color_eyre::Result<()> {
         self.w
             .queue(cursor::MoveTo(0, self.top + self.window_height + 1))?
-            .queue(terminal::Clear(ClearType::CurrentLine))?
-            .queue(style::Print("Enter (d)elete, (c)omplete, (q)uit."))?;
+            .queue(terminal::Clear(ClearType::CurrentLine))?;
+
+        if let Some(message) = &self.message {
+            self.w
+                .queue(style::PrintStyledContent(message.as_str().red()))?;
+            return Ok(());
+        }
+
+        if matches!(self.mode, Mode::Add | Mode::Edit(_))
+            && !self.buffer.trim().is_empty()
+            && let Err(err) = self.parse()
+        {
+            self.w.queue(style::PrintStyledContent(err.red()))?;
+            return Ok(());
+        }
+
+        let help = match self.mode {
+            Mode::Normal => "(a)dd, (e)dit, (c)omplete, (d)elete, (/) search, ctrl-s save, (q)uit",
+            Mode::Search => "Type to filter, enter to keep, esc to clear",
+            Mode::Add | Mode::Edit(_) => "todo.txt line, enter to accept, esc to cancel",
+        };
+        self.w.queue(style::PrintStyledContent(help.dark_grey()))?;
+
         Ok(())
     }
 
-    fn render_list(&mut self, position: usize, current: u16) -> color_eyre::Result<()> {
-        let items = self
-            .collection
+    fn render_list(&mut self) -> color_eyre::Result<()> {
+        let rows = self
+            .visible
             .iter()
-            .skip(position)
-            .take(self.window_height as usize);
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.window_height as usize)
+            .map(|(row, &idx)| (row, idx))
+            .collect::<Vec<_>>();
 
-        self.w.queue(cursor::MoveTo(0, self.top))?;
         let mut last = 0;
-        for (idx, item) in items.enumerate() {
+        for (row, idx) in rows {
+            let item = &self.entries[idx].todo;
+
             self.w
-                .queue(cursor::MoveToNextLine(1))?
+                .queue(cursor::MoveTo(0, self.top + 1 + last))?
                 .queue(terminal::Clear(ClearType::CurrentLine))?;
 
-            if idx == current as usize {
+            if row == self.selected {
                 self.w.queue(style::PrintStyledContent(
                     ">".attribute(style::Attribute::Bold),
                 ))?;
             } else {
-                self.w.queue(cursor::MoveRight(1))?;
+                self.w.queue(style::Print(" "))?;
             }
 
-            self.w
-                .queue(cursor::MoveRight(1))?
-                .queue(if item.done {
-                    style::Print("[x]")
-                } else {
-                    style::Print("[ ]")
-                })?
-                .queue(style::Print(&item.description))?;
-
-            for project in &item.projects {
-                self.w.queue(style::PrintStyledContent(
-                    format!(" +{project}")
-                        .with(style::Color::Magenta)
-                        .attribute(style::Attribute::Bold),
-                ))?;
-            }
+            // Same colors as `todo list`
+            self.w.queue(style::Print(format!(
+                " {}",
+                render_todo(item, self.clock.today())
+            )))?;
 
             last += 1;
         }
 
-        for _ in last..self.window_height {
+        for row in last..self.window_height {
             self.w
-                .queue(cursor::MoveToNextLine(1))?
+                .queue(cursor::MoveTo(0, self.top + 1 + row))?
                 .queue(terminal::Clear(ClearType::CurrentLine))?;
         }
 
         Ok(())
     }
-
-    fn set_input(&mut self, input: &str) -> color_eyre::Result<()> {
-        self.buffer = input.to_string();
-        self.w
-            .queue(cursor::MoveTo(0, self.top))?
-            .queue(style::Print("> "))?
-            .queue(style::Print(input))?;
-        Ok(())
-    }
 }
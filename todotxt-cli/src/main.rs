@@ -5,43 +5,90 @@ use std::{
 };
 
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use color_eyre::{eyre::eyre, owo_colors::OwoColorize};
-use directories::ProjectDirs;
-use editor2::ListBox;
-use inquire::Text;
-use projects::Projects;
-use todotxt::{Collection, Todo, parser::parse};
+use color_eyre::owo_colors::OwoColorize;
+use inquire::{Confirm, Text};
+use projects::{
+    ConflictStrategy, DirStorage, Field, FileStorage, Filter, ImportMode, OpenOptions, Projects,
+    Status, Storage, Template, TodoRef,
+    report::{Format, Report},
+};
+use todotxt::{Clock, Collection, EnvClock, Todo, ical, parser::parse, quick};
 
 mod editor;
-mod editor2;
+mod tui;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
     let matches = clap::Command::new("Todo.txt")
-        .arg(Arg::new("file").short('f'))
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .help("Use a single todo.txt file, projects are +tags"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Use a named profile from the config file"),
+        )
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .help("Use the projects in this directory"),
+        )
         .subcommand(
             clap::Command::new("new")
                 .alias("n")
                 .arg(Arg::new("todo").help("Todo in todo.txt format"))
+                .arg(
+                    Arg::new("quick")
+                        .long("quick")
+                        .short('q')
+                        .action(ArgAction::SetTrue)
+                        .help("Read dates at the end, e.g. \"email Sara +hiring tomorrow 3pm\""),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .short('y')
+                        .action(ArgAction::SetTrue)
+                        .help("Create the todo without confirmation"),
+                )
                 .about("Create a new todo"),
         )
         .subcommand(
             clap::Command::new("list")
                 .alias("l")
-                .arg(Arg::new("context").short('c'))
-                .arg(Arg::new("project").short('p').help("Filter by project"))
+                .arg(
+                    Arg::new("context")
+                        .short('c')
+                        .help("Only todos with this @context"),
+                )
+                .arg(
+                    Arg::new("project")
+                        .short('p')
+                        .help("Only todos listed in this project"),
+                )
                 .arg(
                     Arg::new("all")
                         .short('a')
                         .action(ArgAction::SetTrue)
                         .help("Also list completed todos"),
                 )
+                .arg(
+                    Arg::new("group")
+                        .short('g')
+                        .long("group")
+                        .value_parser(["project", "context", "due"])
+                        .help("Group the todos"),
+                )
+                .args(project_filter_args())
                 .about("List todos"),
         )
+        .subcommand(Command::new("tui").about("Browse and triage every project full screen"))
         .subcommand(
             clap::Command::new("edit")
                 .alias("e")
+                .about("Edit the todos of a project interactively")
                 .arg(Arg::new("project").required(true)),
         )
         .subcommand(
@@ -49,22 +96,266 @@ fn main() -> color_eyre::Result<()> {
                 .alias("r")
                 .arg(Arg::new("project").required(true)),
         )
+        .subcommand(
+            Command::new("report")
+                .about("Render a project status report")
+                .arg(Arg::new("project").required(true))
+                .arg(
+                    Arg::new("html")
+                        .long("html")
+                        .action(ArgAction::SetTrue)
+                        .help("Render static HTML instead of Markdown"),
+                )
+                .arg(
+                    Arg::new("days")
+                        .short('d')
+                        .value_parser(clap::value_parser!(i64))
+                        .help("Days of completed todos to include"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .help("Write to file instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("ics")
+                .about("Export or import todos as iCalendar")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("export")
+                        .arg(Arg::new("project").required(true))
+                        .arg(
+                            Arg::new("output")
+                                .short('o')
+                                .help("Write to file instead of stdout"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .arg(Arg::new("file").required(true))
+                        .arg(Arg::new("project").help("Target project, defaults to +projects")),
+                ),
+        )
+        .subcommand(
+            Command::new("project")
+                .about("Manage projects")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("new")
+                        .about("Create a project")
+                        .arg(Arg::new("project").required(true))
+                        .arg(
+                            Arg::new("template")
+                                .long("template")
+                                .short('t')
+                                .help("Start from a template in the config directory"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("List projects with their status and open todos")
+                        .args(project_filter_args()),
+                )
+                .subcommand(Command::new("templates").about("List the project templates"))
+                .subcommand(
+                    Command::new("rename")
+                        .about("Rename a project and retag its todos")
+                        .arg(Arg::new("from").required(true))
+                        .arg(Arg::new("to").required(true)),
+                )
+                .subcommand(
+                    Command::new("delete")
+                        .about("Move a project to the trash")
+                        .arg(Arg::new("project").required(true)),
+                )
+                .subcommand(
+                    Command::new("merge")
+                        .about("Merge a project into another")
+                        .arg(Arg::new("from").required(true))
+                        .arg(Arg::new("into").required(true)),
+                )
+                .subcommand(
+                    Command::new("archive")
+                        .about("Archive a project")
+                        .arg(Arg::new("project").required(true)),
+                )
+                .subcommand(
+                    Command::new("unarchive")
+                        .about("Restore an archived project")
+                        .arg(Arg::new("project").required(true)),
+                )
+                .subcommand(
+                    Command::new("link")
+                        .about("Link a directory to a project")
+                        .arg(Arg::new("project").required(true))
+                        .arg(Arg::new("path").help("Defaults to the current directory")),
+                )
+                .subcommand(
+                    Command::new("unlink")
+                        .about("Remove a linked directory")
+                        .arg(Arg::new("project").required(true))
+                        .arg(Arg::new("path").help("Defaults to the current directory")),
+                )
+                .subcommand(
+                    Command::new("consolidate")
+                        .about("Collapse todos copied into several projects into one"),
+                ),
+        )
+        .subcommand(
+            Command::new("harvest")
+                .about("Collect TODO, FIXME and XXX comments from linked directories")
+                .arg(Arg::new("project").help("Defaults to the current project")),
+        )
+        .subcommand(
+            Command::new("checklist")
+                .about("Sync README checklists with the todos")
+                .arg(
+                    Arg::new("project")
+                        .help("Defaults to every project with checklist = true in project.toml"),
+                ),
+        )
+        .subcommand(
+            Command::new("search")
+                .alias("s")
+                .about("Find projects and todos mentioning some words")
+                .arg(Arg::new("terms").required(true).num_args(1..)),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("List the changes to a project")
+                .arg(Arg::new("project").required(true))
+                .arg(
+                    Arg::new("rev")
+                        .short('r')
+                        .help("Print the todos at this revision"),
+                ),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Restore a project to an earlier revision")
+                .arg(Arg::new("project").required(true))
+                .arg(Arg::new("rev").required(true)),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Copy every project into another store or a .tar bundle")
+                .arg(
+                    Arg::new("target")
+                        .required(true)
+                        .help("Directory, todo.txt file, .db SQLite database or .tar bundle"),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Copy every project from another store or a .tar bundle")
+                .arg(
+                    Arg::new("source")
+                        .required(true)
+                        .help("Directory, todo.txt file, .db SQLite database or .tar bundle"),
+                )
+                .arg(
+                    Arg::new("replace")
+                        .long("replace")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Delete the current projects before importing, only for .tar bundles",
+                        ),
+                ),
+        )
         .get_matches();
 
-    let mut projects = Projects::open()?;
+    let mut options = OpenOptions::new();
+    if let Some(file) = matches.get_one::<String>("file") {
+        options.file(file);
+    }
+    if let Some(dir) = matches.get_one::<String>("dir") {
+        options.root(dir);
+    }
+    if let Some(profile) = matches.get_one::<String>("profile") {
+        options.profile(profile);
+    }
+
+    let mut projects = options.open()?;
+    for name in projects.invalid() {
+        eprintln!("Skipping {name:?}: not a valid project name");
+    }
+    // Todos added elsewhere while we were running shouldn't fail the command
+    projects.set_conflict_strategy(ConflictStrategy::Merge);
+    let clock = EnvClock::new()?;
 
     match matches.subcommand() {
         Some(("new", new_args)) => {
-            create_todo(&mut projects, new_args)?;
+            create_todo(&mut projects, &clock, new_args)?;
         }
         Some(("list", list_args)) => {
-            list_todos(&mut projects, list_args)?;
+            list_todos(&mut projects, &clock, list_args)?;
         }
         Some(("readme", readme_args)) => {
-            readme(&mut projects, readme_args)?;
+            readme(&mut projects, &clock, readme_args)?;
+        }
+        Some(("tui", _)) => {
+            tui::run(&mut projects, &clock)?;
         }
         Some(("edit", list_args)) => {
-            edit_todos(&mut projects, list_args)?;
+            edit_todos(&mut projects, &clock, list_args)?;
+        }
+        Some(("report", report_args)) => {
+            report(&mut projects, &clock, report_args)?;
+        }
+        Some(("ics", ics_args)) => {
+            ics(&mut projects, &clock, ics_args)?;
+        }
+        Some(("project", project_args)) => {
+            project(&mut projects, &clock, project_args)?;
+        }
+        Some(("harvest", harvest_args)) => {
+            harvest(&mut projects, &clock, harvest_args)?;
+        }
+        Some(("checklist", checklist_args)) => {
+            checklist(&mut projects, &clock, checklist_args)?;
+        }
+        Some(("search", search_args)) => {
+            search(&mut projects, search_args)?;
+        }
+        Some(("history", history_args)) => {
+            history(&projects, history_args)?;
+        }
+        Some(("restore", restore_args)) => {
+            let project_name = restore_args.get_one::<String>("project").unwrap();
+            let rev = restore_args.get_one::<String>("rev").unwrap();
+            projects.restore(project_name, rev)?;
+            projects.sync()?;
+        }
+        Some(("export", export_args)) => {
+            let target = export_args.get_one::<String>("target").unwrap();
+            let count = if is_bundle(target) {
+                projects.export(fs::File::create(target)?)?
+            } else {
+                copy_into_new(projects.storage(), target)?
+            };
+            println!("Exported {count} projects");
+        }
+        Some(("import", import_args)) => {
+            let source = import_args.get_one::<String>("source").unwrap();
+            let replace = import_args.get_flag("replace");
+            let count = if is_bundle(source) {
+                let mode = if replace {
+                    ImportMode::Replace
+                } else {
+                    ImportMode::Merge
+                };
+                projects.import(fs::File::open(source)?, mode)?
+            } else if replace {
+                return Err(color_eyre::eyre::eyre!(
+                    "--replace only works when importing a .tar bundle"
+                ));
+            } else {
+                let copied = projects::copy(&*open_storage(source)?, projects.storage())?;
+                warn_skipped(&copied);
+                copied.projects
+            };
+            println!("Imported {count} projects");
         }
         _ => {}
     };
@@ -72,7 +363,13 @@ fn main() -> color_eyre::Result<()> {
     Ok(())
 }
 
-fn create_todo(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
+fn create_todo(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    let interactive = args.get_one::<String>("todo").is_none();
+
     let input = if let Some(todo) = args.get_one::<String>("todo") {
         todo.trim().to_string()
     } else {
@@ -88,25 +385,35 @@ fn create_todo(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result
         input.trim().to_string()
     };
 
-    let mut todo = Todo::from(parse(&input)?)?;
-    todo.created = Some(chrono::Local::now().date_naive());
+    let mut todo = if args.get_flag("quick") {
+        quick::parse(&input, clock)?
+    } else {
+        Todo::from(parse(&input)?)?
+    };
+    todo.created.get_or_insert(clock.today());
 
     if todo.projects.is_empty() {
-        eprintln!("No project specified");
-        return Ok(());
+        let Some(project_name) = current_project(projects) else {
+            eprintln!("No project specified");
+            return Ok(());
+        };
+        todo.projects.push(project_name);
     }
 
-    for project_name in &todo.projects {
-        let project = if let Some(project) = projects.find_mut(&*project_name) {
-            project
-        } else {
-            projects.create(project_name.clone()).unwrap()
-        };
+    if (interactive || !args.get_flag("yes")) && std::io::stdin().is_terminal() {
+        println!("{todo}");
+
+        let confirmed = Confirm::new("Create todo?")
+            .with_default(true)
+            .prompt_skippable()?;
 
-        project.todos_mut().create_todo(todo.clone());
+        if confirmed != Some(true) {
+            return Ok(());
+        }
     }
 
-    projects.sync().unwrap();
+    projects.add_todo(todo)?;
+    projects.sync()?;
 
     Ok(())
 }
@@ -137,76 +444,747 @@ impl inquire::Autocomplete for AutoCompleter {
     }
 }
 
-fn list_todos(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
+fn project_filter_args() -> [Arg; 4] {
+    [
+        Arg::new("archived")
+            .long("archived")
+            .action(ArgAction::SetTrue)
+            .help("Include archived projects"),
+        Arg::new("status")
+            .long("status")
+            .value_parser(Status::ALL.map(|m| m.as_str()))
+            .help("Only projects with this status"),
+        Arg::new("owner")
+            .long("owner")
+            .help("Only projects with this owner"),
+        Arg::new("tag")
+            .long("tag")
+            .help("Only projects with this tag"),
+    ]
+}
+
+fn project_filter(args: &ArgMatches) -> Filter {
+    Filter {
+        status: args
+            .get_one::<String>("status")
+            .and_then(|m| Status::from_name(m)),
+        owner: args.get_one::<String>("owner").cloned(),
+        tag: args.get_one::<String>("tag").cloned(),
+        archived: args.get_flag("archived"),
+        ..Default::default()
+    }
+}
+
+fn list_todos(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    if projects.is_empty() {
+        println!("You have no projects yet...");
+        return Ok(());
+    }
+
+    let project_name = args
+        .get_one::<String>("project")
+        .cloned()
+        .or_else(|| current_project(projects));
+
+    let filter = project_filter(args);
+    // Owner and todo
+    let mut todos = Vec::<(&str, &Todo)>::default();
+    match &project_name {
+        Some(project_name) => {
+            for (todo_ref, todo) in listed(projects, project_name)? {
+                let owner = projects.get(&todo_ref.project)?.name();
+                todos.push((owner, todo));
+            }
+        }
+        None => {
+            for project in projects.filter(&filter) {
+                let project = match project {
+                    Ok(project) => project,
+                    Err(err) => {
+                        eprintln!("{err}");
+                        continue;
+                    }
+                };
+                match project.todos() {
+                    Ok(list) => todos.extend(list.iter().map(|todo| (project.name(), todo))),
+                    Err(err) => eprintln!("{err}"),
+                }
+            }
+        }
+    }
+
+    let context = args.get_one::<String>("context");
+    todos.retain(|(_, todo)| {
+        (args.get_flag("all") || !todo.done)
+            && context.is_none_or(|context| todo.contexts.contains(context))
+    });
+    todos.sort_by_key(|(_, todo)| {
+        (
+            todo.done,
+            todo.priority.is_none(),
+            todo.priority,
+            todo.due().is_none(),
+            todo.due(),
+        )
+    });
+
+    if todos.is_empty() {
+        println!("Nothing to do");
+    }
+
+    let color = std::io::stdout().is_terminal();
+    let today = clock.today();
+
+    // Group name, number and todo, in the order the groups are printed. Todos are
+    // numbered as listed without groups, so a todo in several groups keeps its number.
+    let numbered = todos
+        .iter()
+        .enumerate()
+        .map(|(idx, (owner, todo))| (idx + 1, *owner, *todo));
+    let mut groups = Vec::<(String, usize, &Todo)>::default();
+    match args.get_one::<String>("group").map(String::as_str) {
+        Some("project") => {
+            for (number, owner, todo) in numbered {
+                groups.push((format!("+{owner}"), number, todo));
+            }
+            groups.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        Some("context") => {
+            for (number, _, todo) in numbered {
+                if todo.contexts.is_empty() {
+                    groups.push(("No context".to_string(), number, todo));
+                }
+                for context in &todo.contexts {
+                    groups.push((format!("@{context}"), number, todo));
+                }
+            }
+            // Todos without a context last
+            groups.sort_by_key(|(group, _, _)| (!group.starts_with('@'), group.clone()));
+        }
+        Some("due") => {
+            const BUCKETS: [&str; 6] = [
+                "Overdue",
+                "Today",
+                "Tomorrow",
+                "This week",
+                "Later",
+                "No due date",
+            ];
+            let mut bucketed = numbered
+                .map(|(number, _, todo)| {
+                    let bucket = match todo.due().map(|due| (due - today).num_days()) {
+                        Some(..0) => 0,
+                        Some(0) => 1,
+                        Some(1) => 2,
+                        Some(2..7) => 3,
+                        Some(_) => 4,
+                        None => 5,
+                    };
+                    (bucket, number, todo)
+                })
+                .collect::<Vec<_>>();
+            bucketed.sort_by_key(|(bucket, _, _)| *bucket);
+            groups.extend(
+                bucketed
+                    .into_iter()
+                    .map(|(bucket, number, todo)| (BUCKETS[bucket].to_string(), number, todo)),
+            );
+        }
+        _ => groups.extend(numbered.map(|(number, _, todo)| (String::default(), number, todo))),
+    }
+
+    let width = todos.len().to_string().len();
+    let mut current = None;
+    for (group, number, todo) in &groups {
+        if !group.is_empty() && current != Some(group) {
+            if current.is_some() {
+                println!();
+            }
+            if color {
+                println!("{}", group.underline().bold());
+            } else {
+                println!("{group}");
+            }
+            current = Some(group);
+        }
+
+        let number = format!("{number:>width$}");
+        if color {
+            println!("{} {}", number.dimmed(), render_todo(todo, today));
+        } else {
+            println!("{number} {todo}");
+        }
+    }
+
+    projects.save_cache()?;
+
+    Ok(())
+}
+
+/// A todo in todo.txt order with colored priority, tags and due date.
+fn render_todo(todo: &Todo, today: chrono::NaiveDate) -> String {
+    let mut parts = Vec::default();
+
+    if todo.done {
+        parts.push("x".dimmed().to_string());
+    }
+    if let Some(priority) = todo.priority {
+        let priority = format!("({priority})");
+        parts.push(match priority.as_str() {
+            "(A)" => priority.red().bold().to_string(),
+            "(B)" => priority.yellow().bold().to_string(),
+            "(C)" => priority.green().bold().to_string(),
+            _ => priority.bold().to_string(),
+        });
+    }
+    if let Some(completed) = todo.completed {
+        parts.push(completed.dimmed().to_string());
+    }
+    if let Some(created) = todo.created {
+        parts.push(created.dimmed().to_string());
+    }
+
+    if todo.done {
+        parts.push(todo.description.dimmed().to_string());
+    } else {
+        parts.push(todo.description.clone());
+    }
+
+    for project in &todo.projects {
+        parts.push(format!("+{project}").cyan().to_string());
+    }
+    for context in &todo.contexts {
+        parts.push(format!("@{context}").magenta().to_string());
+    }
+
+    if let Some(due) = todo.due() {
+        let due = format!("due:{due}");
+        parts.push(match todo.due() {
+            _ if todo.done => due.dimmed().to_string(),
+            Some(date) if date < today => due.red().bold().to_string(),
+            Some(date) if date == today => due.yellow().bold().to_string(),
+            _ => due.to_string(),
+        });
+    }
+
+    let mut keys = todo
+        .values
+        .keys()
+        .filter(|m| *m != todotxt::DUE_KEY)
+        .collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+        for value in &todo.values[key] {
+            parts.push(format!("{key}:{value}").dimmed().to_string());
+        }
+    }
+
+    parts.join(" ")
+}
+
+fn list_projects(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
     if projects.is_empty() {
         println!("You have no projects yet...");
         return Ok(());
     }
 
-    if std::io::stdout().is_terminal() {
+    let color = std::io::stdout().is_terminal();
+    if color {
         println!("{}", "Projects".underline().bold());
     }
-    for project in projects.iter() {
-        println!("{}", project.name());
+    for project in projects.filter(&project_filter(args)) {
+        let project = match project {
+            Ok(project) => project,
+            Err(err) => {
+                eprintln!("{err}");
+                continue;
+            }
+        };
+
+        let mut details = Vec::default();
+        let metadata = project.metadata()?;
+        if metadata.status != Status::Active {
+            details.push(metadata.status.to_string());
+        }
+        if let Some(target) = metadata.target {
+            details.push(format!("target {target}"));
+        }
+        match project.summary() {
+            Ok(summary) => details.push(format!("{} open", summary.open)),
+            Err(err) => eprintln!("{err}"),
+        }
+
+        if details.is_empty() {
+            println!("{}", project.name());
+        } else if color {
+            println!("{} {}", project.name(), details.join(", ").dimmed());
+        } else {
+            println!("{} ({})", project.name(), details.join(", "));
+        }
     }
 
+    projects.save_cache()?;
+
     Ok(())
 }
 
-fn readme(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
+fn is_bundle(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|m| m == "tar")
+}
+
+/// Export into `target`. A new target is written next to it first and moved
+/// into place once complete, so a failed export leaves nothing behind.
+fn copy_into_new(from: &dyn Storage, target: &str) -> color_eyre::Result<usize> {
+    let target = Path::new(target);
+    if target.exists() {
+        let copied = projects::copy(from, &*open_storage(target)?)?;
+        warn_skipped(&copied);
+        return Ok(copied.projects);
+    }
+
+    // Same name in a scratch directory, which picks the same kind of store
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let scratch = target.with_file_name(format!(".tmp-{file_name}"));
+    let temp = scratch.join(&*file_name);
+    fs::create_dir_all(&scratch)?;
+
+    let result = projects::copy(from, &*open_storage(&temp)?).and_then(|copied| {
+        // Nothing is written for an empty export
+        if temp.exists() {
+            fs::rename(&temp, target)?;
+        }
+        Ok(copied)
+    });
+    let _ = fs::remove_dir_all(&scratch);
+    let copied = result?;
+
+    warn_skipped(&copied);
+    Ok(copied.projects)
+}
+
+fn warn_skipped(copied: &projects::Copied) {
+    for (location, part) in &copied.skipped {
+        eprintln!(
+            "Skipped {} of +{}: not supported by the target",
+            part.file_name(),
+            location.name
+        );
+    }
+}
+
+/// Pick a store by path: SQLite for `.db` files, a plain todo.txt for
+/// `.txt` and other existing files and a projects directory otherwise.
+fn open_storage(path: impl Into<PathBuf>) -> color_eyre::Result<Box<dyn Storage>> {
+    let path = path.into();
+    let extension = path
+        .extension()
+        .and_then(|m| m.to_str())
+        .unwrap_or_default();
+
+    if matches!(extension, "db" | "sqlite" | "sqlite3") {
+        #[cfg(feature = "sqlite")]
+        return Ok(Box::new(projects::SqliteStorage::open(path)?));
+        #[cfg(not(feature = "sqlite"))]
+        return Err(color_eyre::eyre::eyre!("Built without SQLite support"));
+    }
+
+    if extension == "txt" || path.is_file() {
+        return Ok(Box::new(FileStorage::open(path)));
+    }
+
+    Ok(Box::new(DirStorage::open(path)?))
+}
+
+/// The project the working directory belongs to, if any.
+fn current_project(projects: &Projects) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    projects.discover(&cwd)
+}
+
+fn readme(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
     let project_name = args.get_one::<String>("project").unwrap();
 
-    let project = if let Some(project) = projects.find_mut(&*project_name) {
-        project
-    } else {
-        projects.create(project_name.clone()).unwrap()
-    };
+    let project = projects.get_or_create(project_name)?;
 
     let out = inquire::Editor::new("Readme")
         .with_file_extension("md")
-        .with_predefined_text(&project.description())
+        .with_predefined_text(project.description()?)
         .prompt_skippable()?;
 
     let Some(out) = out else { return Ok(()) };
 
-    *project.description_mut() = out;
+    *project.description_mut()? = out;
+    if project.metadata()?.checklist {
+        project.sync_checklist(clock)?;
+    }
+
+    projects.sync()?;
+
+    Ok(())
+}
+
+/// [`Projects::listed`], reporting the projects left out.
+fn listed<'a>(
+    projects: &'a Projects,
+    name: &str,
+) -> color_eyre::Result<Vec<(TodoRef, &'a Todo)>> {
+    let listed = projects.listed(name)?;
+    for (project, err) in &listed.skipped {
+        eprintln!("Skipping +{project}: {err}");
+    }
+    Ok(listed.todos)
+}
+
+fn report(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    let project_name = args.get_one::<String>("project").unwrap();
+    let project = projects.get(project_name)?;
+
+    let mut report = Report::new(project, clock)?.todos(
+        listed(projects, project_name)?
+            .into_iter()
+            .map(|(_, todo)| todo),
+    );
+    if let Some(days) = args.get_one::<i64>("days") {
+        report = report.recent_days(*days);
+    }
+
+    let output = report.render(if args.get_flag("html") {
+        Format::Html
+    } else {
+        Format::Markdown
+    });
+
+    if let Some(path) = args.get_one::<String>("output") {
+        fs::write(path, output)?;
+    } else {
+        print!("{output}");
+    }
+
+    Ok(())
+}
+
+fn ics(projects: &mut Projects, clock: &impl Clock, args: &ArgMatches) -> color_eyre::Result<()> {
+    match args.subcommand() {
+        Some(("export", export_args)) => {
+            let project_name = export_args.get_one::<String>("project").unwrap();
+            let project = projects.get(project_name)?;
+
+            let todos = listed(projects, project.name())?
+                .into_iter()
+                .map(|(_, todo)| todo.clone())
+                .collect::<Collection>();
+            let output = ical::to_ics(&todos, clock);
+
+            if let Some(path) = export_args.get_one::<String>("output") {
+                fs::write(path, output)?;
+            } else {
+                print!("{output}");
+            }
+        }
+        Some(("import", import_args)) => {
+            let path = import_args.get_one::<String>("file").unwrap();
+            let target = import_args.get_one::<String>("project");
+            let imported = ical::from_ics(&fs::read_to_string(path)?)?;
+
+            let mut count = 0;
+            for mut todo in imported.iter().cloned() {
+                let Some(project_name) = target.or(todo.projects.first()).cloned() else {
+                    eprintln!("Skipping todo without project: {}", todo.description);
+                    continue;
+                };
+
+                if !todo.projects.contains(&project_name) {
+                    todo.projects.insert(0, project_name.clone());
+                }
+
+                let project = projects.get_or_create(&project_name)?;
+
+                let uid = todo.get(ical::UID_KEY);
+                let exists = project.todos()?.iter().any(|m| {
+                    (uid.is_some() && m.get(ical::UID_KEY) == uid)
+                        || (m.description == todo.description && m.created == todo.created)
+                });
+
+                if exists {
+                    continue;
+                }
+
+                project.todos_mut()?.create_todo(todo);
+                count += 1;
+            }
+
+            projects.sync()?;
+            println!("Imported {count} todos");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn project(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    fn arg<'a>(args: &'a ArgMatches, name: &str) -> &'a str {
+        args.get_one::<String>(name).unwrap()
+    }
+
+    match args.subcommand() {
+        Some(("new", args)) => {
+            let name = arg(args, "project").to_string();
+            match args.get_one::<String>("template") {
+                Some(template) => projects.create_from_template(name, template, clock)?,
+                None => projects.create(name)?,
+            };
+            projects.sync()?;
+        }
+        Some(("list", args)) => {
+            list_projects(projects, args)?;
+        }
+        Some(("templates", _)) => {
+            for name in Template::list(projects.config_dir())? {
+                println!("{name}");
+            }
+        }
+        Some(("rename", args)) => {
+            projects.rename(arg(args, "from"), arg(args, "to"))?;
+        }
+        Some(("delete", args)) => {
+            let name = arg(args, "project");
+            if let Some(trash) = projects.delete(name)? {
+                println!("Moved {name} to {}", trash.display());
+            }
+        }
+        Some(("merge", args)) => {
+            projects.merge(arg(args, "from"), arg(args, "into"))?;
+        }
+        Some(("archive", args)) => {
+            projects.archive(arg(args, "project"))?;
+        }
+        Some(("unarchive", args)) => {
+            projects.unarchive(arg(args, "project"))?;
+        }
+        Some(("link", args)) => {
+            let path = link_path(args)?;
+            if !projects.link(arg(args, "project"), &path)? {
+                println!("{} is already linked", path.display());
+            }
+        }
+        Some(("unlink", args)) => {
+            let path = link_path(args)?;
+            if !projects.unlink(arg(args, "project"), &path)? {
+                println!("{} is not linked", path.display());
+            }
+        }
+        Some(("consolidate", _)) => {
+            let removed = projects.consolidate()?;
+            projects.sync()?;
+            println!("Removed {removed} copies");
+        }
+        _ => {}
+    }
 
+    Ok(())
+}
+
+fn link_path(args: &ArgMatches) -> std::io::Result<PathBuf> {
+    match args.get_one::<String>("path") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => std::env::current_dir(),
+    }
+}
+
+fn harvest(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    let Some(project_name) = args
+        .get_one::<String>("project")
+        .cloned()
+        .or_else(|| current_project(projects))
+    else {
+        eprintln!("No project specified");
+        return Ok(());
+    };
+
+    let harvest = projects.harvest(&project_name, clock)?;
     projects.sync()?;
 
+    println!(
+        "Added {}, moved {}, completed {}, reopened {}",
+        harvest.added, harvest.moved, harvest.completed, harvest.reopened
+    );
+
+    Ok(())
+}
+
+fn checklist(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    let checklist = match args.get_one::<String>("project") {
+        Some(project_name) => projects.get_mut(project_name)?.sync_checklist(clock)?,
+        None => projects.sync_checklists(clock)?,
+    };
+    projects.sync()?;
+
+    println!(
+        "Added {}, checked {}, unchecked {}, completed {}, reopened {}",
+        checklist.added,
+        checklist.checked,
+        checklist.unchecked,
+        checklist.completed,
+        checklist.reopened
+    );
+
+    Ok(())
+}
+
+fn search(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
+    let query = args
+        .get_many::<String>("terms")
+        .unwrap()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let results = projects.search(&query)?;
+    for (project, err) in &results.skipped {
+        eprintln!("Skipping +{project}: {err}");
+    }
+
+    let hits = results.hits;
+    if hits.is_empty() {
+        println!("Nothing found");
+        return Ok(());
+    }
+
+    let color = std::io::stdout().is_terminal();
+    for hit in hits {
+        let place = match hit.field {
+            Field::Description => format!("{} README", hit.project),
+            Field::Todo(index) => format!("{} #{}", hit.project, index + 1),
+        };
+
+        if !color {
+            println!("{place}: {}", hit.snippet);
+            continue;
+        }
+
+        let mut snippet = String::default();
+        let mut last = 0;
+        for range in &hit.highlights {
+            snippet.push_str(&hit.snippet[last..range.start]);
+            let word = &hit.snippet[range.clone()];
+            snippet.push_str(&word.yellow().bold().to_string());
+            last = range.end;
+        }
+        snippet.push_str(&hit.snippet[last..]);
+        println!("{} {snippet}", format!("{place}:").dimmed());
+    }
+
     Ok(())
 }
 
-// fn edit_todos(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
-//     let project_name = args.get_one::<String>("project").unwrap();
+fn history(projects: &Projects, args: &ArgMatches) -> color_eyre::Result<()> {
+    let project_name = args.get_one::<String>("project").unwrap();
 
-//     let project = if let Some(project) = projects.find_mut(&*project_name) {
-//         project
-//     } else {
-//         projects.create(project_name.clone()).unwrap()
-//     };
+    if let Some(rev) = args.get_one::<String>("rev") {
+        let project = projects.show(project_name, rev)?;
+        print!("{}", project.todos()?);
+        return Ok(());
+    }
 
-//     if !editor::run(project.todos_mut())? {
-//         return Ok(());
-//     }
+    let color = std::io::stdout().is_terminal();
+    for revision in projects.history(project_name)? {
+        let date = revision.date.format("%Y-%m-%d %H:%M");
+        if color {
+            println!(
+                "{} {} {}",
+                revision.short_id().yellow(),
+                date.dimmed(),
+                revision.message
+            );
+        } else {
+            println!("{} {} {}", revision.short_id(), date, revision.message);
+        }
+    }
 
-//     projects.sync()?;
+    Ok(())
+}
 
-//     Ok(())
-// }
+fn edit_todos(
+    projects: &mut Projects,
+    clock: &impl Clock,
+    args: &ArgMatches,
+) -> color_eyre::Result<()> {
+    let project_name = args.get_one::<String>("project").unwrap();
+
+    let listed = listed(projects, project_name)?
+        .into_iter()
+        .map(|(todo_ref, todo)| (todo_ref, todo.clone()))
+        .collect::<Vec<_>>();
+    let mut entries = listed
+        .iter()
+        .map(|(todo_ref, todo)| editor::Entry {
+            origin: Some(todo_ref.clone()),
+            todo: todo.clone(),
+        })
+        .collect::<Vec<_>>();
 
-fn edit_todos(projects: &mut Projects, args: &ArgMatches) -> color_eyre::Result<()> {
-    for _ in 0..5 {
-        println!();
+    if !editor::run(&mut entries, project_name, clock)? {
+        return Ok(());
     }
 
-    let (_, current_row) = crossterm::cursor::position()?;
-    let start = current_row - 5;
+    // Todos are saved in their owners, so a todo whose first project changed moves there
+    let mut changes = Vec::default();
+    let mut added = Vec::default();
+    for entry in entries {
+        match entry.origin {
+            Some(origin) => changes.push((origin, Some(entry.todo))),
+            None => added.push(entry.todo),
+        }
+    }
+    for (todo_ref, _) in listed {
+        if !changes.iter().any(|(origin, _)| *origin == todo_ref) {
+            changes.push((todo_ref, None));
+        }
+    }
+
+    // Last first, so moving or removing a todo doesn't shift the ones still to do
+    changes.sort_by_key(|(todo_ref, _)| std::cmp::Reverse(todo_ref.index));
+    for (todo_ref, todo) in changes {
+        match todo {
+            Some(todo) if projects.todo(&todo_ref)? != Some(&todo) => {
+                projects.update_todo(&todo_ref, todo)?;
+            }
+            Some(_) => {}
+            None => {
+                projects.remove_todo(&todo_ref)?;
+            }
+        }
+    }
+    for todo in added {
+        projects.add_todo(todo)?;
+    }
 
-    editor2::run(&mut ListBox::new(
-        vec!["Hello".to_string(), "World".to_string()],
-        5,
-        start,
-    ))?;
+    projects.sync()?;
 
     Ok(())
 }
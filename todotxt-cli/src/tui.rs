@@ -0,0 +1,707 @@
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    QueueableCommand, cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{self, ContentStyle, StyledContent, Stylize},
+    terminal::{self, ClearType},
+};
+use projects::{Projects, TodoRef};
+use todotxt::{Clock, Todo, parser::parse};
+
+/// How often projects are checked for changes made elsewhere.
+const REFRESH: Duration = Duration::from_secs(2);
+
+/// Full screen dashboard with projects, the todos of the selected one and its README.
+pub fn run(projects: &mut Projects, clock: &impl Clock) -> color_eyre::Result<()> {
+    let (width, height) = terminal::size()?;
+    let mut tui = Tui {
+        projects,
+        clock,
+        w: io::stdout(),
+        width,
+        height,
+        focus: Pane::Projects,
+        project_filter: String::new(),
+        todo_filter: String::new(),
+        selected_project: 0,
+        selected_todo: 0,
+        project_scroll: 0,
+        todo_scroll: 0,
+        readme_scroll: 0,
+        input: None,
+        message: None,
+    };
+
+    terminal::enable_raw_mode()?;
+    tui.w
+        .queue(terminal::EnterAlternateScreen)?
+        .queue(cursor::Hide)?
+        .flush()?;
+
+    let ret = tui.run_inner();
+
+    tui.w
+        .queue(cursor::Show)?
+        .queue(terminal::LeaveAlternateScreen)?
+        .flush()?;
+    terminal::disable_raw_mode()?;
+
+    ret
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Projects,
+    Todos,
+    Readme,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Filter,
+    NewProject,
+    NewTodo,
+}
+
+struct Input {
+    prompt: Prompt,
+    buffer: String,
+}
+
+struct Tui<'a, C> {
+    projects: &'a mut Projects,
+    clock: &'a C,
+    w: io::Stdout,
+    width: u16,
+    height: u16,
+    focus: Pane,
+    project_filter: String,
+    todo_filter: String,
+    selected_project: usize,
+    selected_todo: usize,
+    project_scroll: usize,
+    todo_scroll: usize,
+    readme_scroll: usize,
+    input: Option<Input>,
+    /// Shown in the status line until the next key.
+    message: Option<String>,
+}
+
+impl<C: Clock> Tui<'_, C> {
+    fn run_inner(&mut self) -> color_eyre::Result<()> {
+        self.render()?;
+
+        loop {
+            if !event::poll(REFRESH)? {
+                // Pick up edits made in other programs, keeping what we have on errors
+                let message = match self.projects.refresh() {
+                    Ok(changes) if changes.is_empty() => continue,
+                    Ok(_) => "Reloaded changes from disk".to_string(),
+                    Err(err) => format!("Refresh failed: {err}"),
+                };
+                if self.message.as_ref() != Some(&message) {
+                    self.message = Some(message);
+                    self.render()?;
+                }
+                continue;
+            }
+
+            match event::read()? {
+                Event::Resize(width, height) => {
+                    self.width = width;
+                    self.height = height;
+                }
+                Event::Key(key) => {
+                    self.message = None;
+                    let result = if self.input.is_some() {
+                        self.input(key)
+                    } else {
+                        match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => break,
+                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                break;
+                            }
+                            _ => self.key(key),
+                        }
+                    };
+                    if let Err(err) = result {
+                        self.message = Some(err.to_string());
+                    }
+                }
+                _ => continue,
+            }
+
+            self.render()?;
+        }
+
+        Ok(())
+    }
+
+    fn key(&mut self, key: KeyEvent) -> color_eyre::Result<()> {
+        match key.code {
+            KeyCode::Tab | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Pane::Projects => Pane::Todos,
+                    Pane::Todos => Pane::Readme,
+                    Pane::Readme => Pane::Projects,
+                };
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                self.focus = match self.focus {
+                    Pane::Projects => Pane::Readme,
+                    Pane::Todos => Pane::Projects,
+                    Pane::Readme => Pane::Todos,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-(self.body_height() as isize)),
+            KeyCode::PageDown => self.move_by(self.body_height() as isize),
+            KeyCode::Char('/') if self.focus != Pane::Readme => {
+                let buffer = match self.focus {
+                    Pane::Projects => self.project_filter.clone(),
+                    _ => self.todo_filter.clone(),
+                };
+                self.input = Some(Input {
+                    prompt: Prompt::Filter,
+                    buffer,
+                });
+            }
+            KeyCode::Char('n') => {
+                self.input = Some(Input {
+                    prompt: Prompt::NewProject,
+                    buffer: String::new(),
+                });
+            }
+            KeyCode::Char('a') if self.current_project().is_some() => {
+                self.input = Some(Input {
+                    prompt: Prompt::NewTodo,
+                    buffer: String::new(),
+                });
+            }
+            KeyCode::Char('x') | KeyCode::Char('c') if self.focus == Pane::Todos => {
+                if let Some(todo_ref) = self.current_todo()? {
+                    let clock = self.clock;
+                    if let Some(todo) = self.projects.todo_mut(&todo_ref)? {
+                        if todo.done {
+                            todo.reopen();
+                        } else {
+                            todo.complete(clock);
+                        }
+                    }
+                    self.projects.sync()?;
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.focus == Pane::Todos => {
+                if let Some(todo_ref) = self.current_todo()? {
+                    self.projects.remove_todo(&todo_ref)?;
+                    self.projects.sync()?;
+                }
+            }
+            KeyCode::Char('r') => {
+                let changes = self.projects.refresh()?;
+                self.message = Some(format!("{} projects changed on disk", changes.len()));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn input(&mut self, key: KeyEvent) -> color_eyre::Result<()> {
+        let Some(input) = &mut self.input else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                if input.prompt == Prompt::Filter {
+                    input.buffer.clear();
+                    self.apply_filter();
+                }
+                self.input = None;
+            }
+            KeyCode::Enter => {
+                let input = self.input.take().unwrap();
+                match input.prompt {
+                    Prompt::Filter => {}
+                    Prompt::NewProject => self.create_project(input.buffer.trim())?,
+                    Prompt::NewTodo => self.create_todo(input.buffer.trim())?,
+                }
+            }
+            KeyCode::Backspace => {
+                input.buffer.pop();
+                if input.prompt == Prompt::Filter {
+                    self.apply_filter();
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.buffer.push(c);
+                if input.prompt == Prompt::Filter {
+                    self.apply_filter();
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Filters the focused pane while typing.
+    fn apply_filter(&mut self) {
+        let Some(input) = &self.input else {
+            return;
+        };
+
+        match self.focus {
+            Pane::Projects => {
+                self.project_filter = input.buffer.clone();
+                self.select_project(0);
+            }
+            _ => {
+                self.todo_filter = input.buffer.clone();
+                self.selected_todo = 0;
+                self.todo_scroll = 0;
+            }
+        }
+    }
+
+    fn create_project(&mut self, name: &str) -> color_eyre::Result<()> {
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let project = self.projects.create(name.to_string())?;
+        *project.description_mut()? = format!("# {name}\n");
+        self.projects.sync()?;
+
+        self.project_filter.clear();
+        if let Some(idx) = self.project_names().iter().position(|m| m == name) {
+            self.select_project(idx);
+        }
+        Ok(())
+    }
+
+    fn create_todo(&mut self, input: &str) -> color_eyre::Result<()> {
+        let Some(project_name) = self.current_project() else {
+            return Ok(());
+        };
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        let mut todo = Todo::from(parse(input)?)?;
+        todo.created.get_or_insert(self.clock.today());
+        if todo.projects.is_empty() {
+            todo.projects.push(project_name);
+        }
+
+        self.projects.add_todo(todo)?;
+        self.projects.sync()?;
+        Ok(())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let step = |idx: usize| idx.saturating_add_signed(delta);
+        match self.focus {
+            Pane::Projects => self.select_project(step(self.selected_project)),
+            Pane::Todos => {
+                let len = self.todo_count();
+                self.selected_todo = step(self.selected_todo).min(len.saturating_sub(1));
+            }
+            Pane::Readme => self.readme_scroll = step(self.readme_scroll),
+        }
+    }
+
+    fn select_project(&mut self, idx: usize) {
+        let len = self.project_names().len();
+        self.selected_project = idx.min(len.saturating_sub(1));
+        self.selected_todo = 0;
+        self.todo_scroll = 0;
+        self.readme_scroll = 0;
+    }
+
+    fn project_names(&self) -> Vec<String> {
+        let filter = self.project_filter.to_lowercase();
+        self.projects
+            .iter()
+            .map(|m| m.name())
+            .filter(|m| m.to_lowercase().contains(&filter))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn current_project(&self) -> Option<String> {
+        self.project_names().into_iter().nth(self.selected_project)
+    }
+
+    fn todos(&self) -> color_eyre::Result<Vec<(TodoRef, &Todo)>> {
+        let Some(project_name) = self.current_project() else {
+            return Ok(Vec::new());
+        };
+
+        let words = self
+            .todo_filter
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        // Broken projects are marked in the projects pane
+        let mut todos = self.projects.listed(&project_name)?.todos;
+        todos.retain(|(_, todo)| {
+            let text = todo.to_string().to_lowercase();
+            words.iter().all(|word| text.contains(word))
+        });
+        Ok(todos)
+    }
+
+    fn todo_count(&self) -> usize {
+        self.todos().map_or(0, |m| m.len())
+    }
+
+    fn current_todo(&self) -> color_eyre::Result<Option<TodoRef>> {
+        Ok(self
+            .todos()?
+            .into_iter()
+            .nth(self.selected_todo)
+            .map(|(todo_ref, _)| todo_ref))
+    }
+
+    /// Rows between the title and status lines.
+    fn body_height(&self) -> u16 {
+        self.height.saturating_sub(2)
+    }
+
+    fn left_width(&self) -> u16 {
+        (self.width / 3).clamp(12, 32).min(self.width)
+    }
+
+    fn render(&mut self) -> color_eyre::Result<()> {
+        self.w.queue(terminal::Clear(ClearType::All))?;
+
+        if self.width < 20 || self.height < 6 {
+            self.w
+                .queue(cursor::MoveTo(0, 0))?
+                .queue(style::Print("Terminal too small"))?
+                .flush()?;
+            return Ok(());
+        }
+
+        self.render_title()?;
+        self.render_projects()?;
+        self.render_todos()?;
+        self.render_readme()?;
+        self.render_status()?;
+
+        self.w.flush()?;
+        Ok(())
+    }
+
+    fn render_title(&mut self) -> color_eyre::Result<()> {
+        let title = format!(" todo · {} projects", self.projects.len());
+        let line = fit(&title, self.width as usize);
+        self.w
+            .queue(cursor::MoveTo(0, 0))?
+            .queue(style::PrintStyledContent(
+                format!("{line:<width$}", width = self.width as usize)
+                    .reverse()
+                    .bold(),
+            ))?;
+        Ok(())
+    }
+
+    fn render_header(
+        &mut self,
+        pane: Pane,
+        title: &str,
+        x: u16,
+        y: u16,
+        width: u16,
+    ) -> io::Result<()> {
+        let title = fit(&format!(" {title} "), width as usize);
+        let title = format!("{title:─<width$}", width = width as usize);
+        let title = if self.focus == pane {
+            title.bold().with(style::Color::Cyan)
+        } else {
+            title.dark_grey()
+        };
+        self.w
+            .queue(cursor::MoveTo(x, y))?
+            .queue(style::PrintStyledContent(title))?;
+        Ok(())
+    }
+
+    fn render_projects(&mut self) -> color_eyre::Result<()> {
+        let width = self.left_width().saturating_sub(1);
+        let title = if self.project_filter.is_empty() {
+            "Projects".to_string()
+        } else {
+            format!("Projects /{}", self.project_filter)
+        };
+        self.render_header(Pane::Projects, &title, 0, 1, width)?;
+
+        let rows = self.body_height().saturating_sub(1) as usize;
+        let names = self.project_names();
+        self.project_scroll = scroll(self.project_scroll, self.selected_project, rows);
+
+        for (row, name) in names
+            .iter()
+            .enumerate()
+            .skip(self.project_scroll)
+            .take(rows)
+        {
+            let project = self.projects.get(name)?;
+            let (count, broken) = match project.summary() {
+                Ok(summary) => (summary.open.to_string(), false),
+                Err(_) => ("!".to_string(), true),
+            };
+
+            let name_width = (width as usize).saturating_sub(count.len() + 3);
+            let line = format!(
+                "{} {:<name_width$} {count}",
+                if row == self.selected_project {
+                    ">"
+                } else {
+                    " "
+                },
+                fit(name, name_width),
+            );
+            let line = if broken {
+                line.red()
+            } else if project.is_archived() {
+                line.dark_grey()
+            } else if row == self.selected_project {
+                line.bold()
+            } else {
+                line.stylize()
+            };
+
+            self.w
+                .queue(cursor::MoveTo(0, 2 + (row - self.project_scroll) as u16))?
+                .queue(style::PrintStyledContent(line))?;
+        }
+
+        for y in 1..=self.body_height() {
+            self.w
+                .queue(cursor::MoveTo(width, y))?
+                .queue(style::PrintStyledContent("│".dark_grey()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Top half of the right side.
+    fn todo_rows(&self) -> u16 {
+        self.body_height() / 2
+    }
+
+    fn render_todos(&mut self) -> color_eyre::Result<()> {
+        let x = self.left_width();
+        let width = self.width.saturating_sub(x);
+        let title = if self.todo_filter.is_empty() {
+            "Todos".to_string()
+        } else {
+            format!("Todos /{}", self.todo_filter)
+        };
+        self.render_header(Pane::Todos, &title, x, 1, width)?;
+
+        let rows = self.todo_rows().saturating_sub(1) as usize;
+        let todos = match self.todos() {
+            Ok(todos) => todos,
+            Err(err) => {
+                let line = fit(&err.to_string(), width as usize);
+                self.w
+                    .queue(cursor::MoveTo(x, 2))?
+                    .queue(style::PrintStyledContent(line.red()))?;
+                return Ok(());
+            }
+        };
+
+        let scroll_to = scroll(self.todo_scroll, self.selected_todo, rows);
+        let lines = todos
+            .iter()
+            .enumerate()
+            .skip(scroll_to)
+            .take(rows)
+            .map(|(row, (_, todo))| {
+                let mut segments = vec![
+                    (
+                        if row == self.selected_todo && self.focus == Pane::Todos {
+                            "> "
+                        } else {
+                            "  "
+                        }
+                        .to_string(),
+                        ContentStyle::new().bold(),
+                    ),
+                    (
+                        if todo.done { "[x] " } else { "[ ] " }.to_string(),
+                        ContentStyle::new(),
+                    ),
+                ];
+                segments.extend(todo_segments(todo, self.clock));
+                segments
+            })
+            .collect::<Vec<_>>();
+
+        for (row, segments) in lines.into_iter().enumerate() {
+            self.w.queue(cursor::MoveTo(x, 2 + row as u16))?;
+            print_segments(&mut self.w, segments, width as usize)?;
+        }
+        self.todo_scroll = scroll_to;
+
+        Ok(())
+    }
+
+    fn render_readme(&mut self) -> color_eyre::Result<()> {
+        let x = self.left_width();
+        let y = 1 + self.todo_rows();
+        let width = self.width.saturating_sub(x);
+        self.render_header(Pane::Readme, "README", x, y, width)?;
+
+        let Some(project_name) = self.current_project() else {
+            return Ok(());
+        };
+        let description = match self.projects.get(&project_name)?.description() {
+            Ok(description) => description.to_string(),
+            Err(err) => {
+                let line = fit(&err.to_string(), width as usize);
+                self.w
+                    .queue(cursor::MoveTo(x, y + 1))?
+                    .queue(style::PrintStyledContent(line.red()))?;
+                return Ok(());
+            }
+        };
+
+        let rows = self.body_height().saturating_sub(y) as usize;
+        let lines = description.lines().collect::<Vec<_>>();
+        self.readme_scroll = self.readme_scroll.min(lines.len().saturating_sub(1));
+
+        for (row, line) in lines.iter().skip(self.readme_scroll).take(rows).enumerate() {
+            let text = fit(line, width as usize);
+            let text = if line.starts_with('#') {
+                text.bold()
+            } else {
+                text.stylize()
+            };
+            self.w
+                .queue(cursor::MoveTo(x, y + 1 + row as u16))?
+                .queue(style::PrintStyledContent(text))?;
+        }
+
+        Ok(())
+    }
+
+    fn render_status(&mut self) -> color_eyre::Result<()> {
+        self.w.queue(cursor::MoveTo(0, self.height - 1))?;
+
+        if let Some(input) = &self.input {
+            let prompt = match input.prompt {
+                Prompt::Filter => "/",
+                Prompt::NewProject => "New project: ",
+                Prompt::NewTodo => "New todo: ",
+            };
+            let line = fit(&format!("{prompt}{}_", input.buffer), self.width as usize);
+            self.w.queue(style::Print(line))?;
+            return Ok(());
+        }
+
+        if let Some(message) = &self.message {
+            let line = fit(message, self.width as usize);
+            self.w.queue(style::PrintStyledContent(line.yellow()))?;
+            return Ok(());
+        }
+
+        let help = match self.focus {
+            Pane::Todos => "tab pane, / filter, a add, x complete, d delete, n new project, q quit",
+            _ => "tab pane, / filter, a add todo, n new project, r reload, q quit",
+        };
+        let line = fit(help, self.width as usize);
+        self.w.queue(style::PrintStyledContent(line.dark_grey()))?;
+
+        Ok(())
+    }
+}
+
+/// Scroll offset keeping `selected` within `rows` rows.
+fn scroll(offset: usize, selected: usize, rows: usize) -> usize {
+    if selected < offset {
+        selected
+    } else if rows > 0 && selected >= offset + rows {
+        selected + 1 - rows
+    } else {
+        offset
+    }
+}
+
+/// Cut `text` to `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut out = text
+        .chars()
+        .take(width.saturating_sub(1))
+        .collect::<String>();
+    out.push('…');
+    out
+}
+
+/// A todo as colored pieces: priority, description, tags and due date.
+fn todo_segments(todo: &Todo, clock: &impl Clock) -> Vec<(String, ContentStyle)> {
+    let mut segments = Vec::new();
+
+    if let Some(priority) = todo.priority {
+        segments.push((
+            format!("({priority}) "),
+            ContentStyle::new().yellow().bold(),
+        ));
+    }
+
+    let description = if todo.done {
+        ContentStyle::new().dark_grey()
+    } else {
+        ContentStyle::new()
+    };
+    segments.push((todo.description.clone(), description));
+
+    for project in &todo.projects {
+        segments.push((format!(" +{project}"), ContentStyle::new().magenta()));
+    }
+    for context in &todo.contexts {
+        segments.push((format!(" @{context}"), ContentStyle::new().cyan()));
+    }
+    if let Some(due) = todo.due() {
+        let style = if todo.is_overdue(clock) {
+            ContentStyle::new().red()
+        } else {
+            ContentStyle::new().dark_grey()
+        };
+        segments.push((format!(" due:{due}"), style));
+    }
+
+    segments
+}
+
+/// Print styled pieces on one line, cut to `width` characters.
+fn print_segments(
+    w: &mut io::Stdout,
+    segments: Vec<(String, ContentStyle)>,
+    width: usize,
+) -> io::Result<()> {
+    let mut left = width;
+    for (text, style) in segments {
+        if left == 0 {
+            break;
+        }
+        let len = text.chars().count();
+        let text = fit(&text, left);
+        left = left.saturating_sub(len);
+        w.queue(style::PrintStyledContent(StyledContent::new(style, text)))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+
+/// Environment variable overriding the current date, in `YYYY-MM-DD` format.
+pub const TODAY_ENV: &str = "TODO_TODAY";
+
+pub trait Clock {
+    fn today(&self) -> NaiveDate;
+}
+
+impl<T: Clock + ?Sized> Clock for &T {
+    fn today(&self) -> NaiveDate {
+        (**self).today()
+    }
+}
+
+impl<T: Clock + ?Sized> Clock for Box<T> {
+    fn today(&self) -> NaiveDate {
+        (**self).today()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub NaiveDate);
+
+impl Clock for FixedClock {
+    fn today(&self) -> NaiveDate {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn today(&self) -> NaiveDate {
+        chrono::Local::now().date_naive()
+    }
+}
+
+/// The system clock, unless [`TODAY_ENV`] is set.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvClock {
+    today: Option<NaiveDate>,
+}
+
+#[cfg(feature = "std")]
+impl EnvClock {
+    pub fn new() -> Result<EnvClock, chrono::ParseError> {
+        let today = match std::env::var(TODAY_ENV) {
+            Ok(today) if !today.trim().is_empty() => {
+                Some(NaiveDate::parse_from_str(today.trim(), "%Y-%m-%d")?)
+            }
+            _ => None,
+        };
+
+        Ok(EnvClock { today })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for EnvClock {
+    fn today(&self) -> NaiveDate {
+        self.today.unwrap_or_else(|| SystemClock.today())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODAY: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+    fn today(clock: impl Clock) -> NaiveDate {
+        clock.today()
+    }
+
+    #[test]
+    fn fixed_clock_through_references() {
+        let clock = FixedClock(TODAY);
+        assert_eq!(today(clock), TODAY);
+        let by_ref = &clock;
+        assert_eq!(today(by_ref), TODAY);
+        assert_eq!(today(Box::new(clock) as Box<dyn Clock>), TODAY);
+    }
+}
@@ -1,12 +1,15 @@
 use core::fmt;
-use std::{
-    collections::{BTreeSet, HashMap},
-    io::BufRead,
-};
+use std::{collections::BTreeSet, io::BufRead};
 
 use chrono::NaiveDate;
+use indexmap::IndexMap;
+
+use crate::{
+    Clock,
+    parser::{Item, is_key, parse},
+};
 
-use crate::parser::{Item, parse};
+pub const DUE_KEY: &str = "due";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
@@ -24,20 +27,28 @@ impl fmt::Display for Value {
             Value::Date(d) => write!(f, "{d}"),
             Value::Float(i) => write!(f, "{i}"),
             Value::Int(i) => write!(f, "{i}"),
+            Value::String(s) if is_bare(s) => write!(f, "{s}"),
             Value::String(s) => write!(f, "{s:?}"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Strings that parse back as the same string without quotes.
+fn is_bare(value: &str) -> bool {
+    is_key(value) && value != "true" && value != "false"
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Todo {
     pub description: String,
+    pub priority: Option<char>,
     pub created: Option<NaiveDate>,
     pub completed: Option<NaiveDate>,
     pub contexts: Vec<String>,
     pub projects: Vec<String>,
     pub done: bool,
-    pub values: HashMap<String, Vec<Value>>,
+    /// `key:value` pairs in the order they were written.
+    pub values: IndexMap<String, Vec<Value>>,
 }
 
 impl fmt::Display for Todo {
@@ -46,6 +57,10 @@ impl fmt::Display for Todo {
             write!(f, "X ")?;
         }
 
+        if let Some(priority) = self.priority {
+            write!(f, "({}) ", priority)?;
+        }
+
         if let Some(completed) = self.completed {
             write!(f, "{} ", completed)?;
         }
@@ -78,6 +93,7 @@ impl Todo {
     pub fn from(todo: crate::parser::Todo<'_>) -> Result<Todo, udled::Error> {
         let mut out = Todo {
             description: todo.description.as_str().into(),
+            priority: todo.priority.and_then(|m| m.as_str().chars().next()),
             contexts: Vec::default(),
             projects: Default::default(),
             done: todo.done,
@@ -118,43 +134,111 @@ impl Todo {
 
         Ok(out)
     }
+
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.values.get(DUE_KEY)?.iter().find_map(|m| match m {
+            Value::Date(date) => Some(*date),
+            _ => None,
+        })
+    }
+
+    pub fn set_due(&mut self, date: Option<NaiveDate>) {
+        match date {
+            Some(date) => {
+                self.values
+                    .insert(DUE_KEY.to_string(), vec![Value::Date(date)]);
+            }
+            None => {
+                self.values.shift_remove(DUE_KEY);
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)?.first()
+    }
+
+    pub fn complete(&mut self, clock: &impl Clock) {
+        self.done = true;
+        self.completed = Some(clock.today());
+    }
+
+    pub fn reopen(&mut self) {
+        self.done = false;
+        self.completed = None;
+    }
+
+    pub fn is_overdue(&self, clock: &impl Clock) -> bool {
+        !self.done && self.due().is_some_and(|due| due < clock.today())
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ReadError {
+    Io(std::io::Error),
+    Parse { line: usize, error: udled::Error },
 }
 
-#[derive(Default)]
+#[cfg(feature = "std")]
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "{err}"),
+            ReadError::Parse { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct Collection {
     todos: Vec<Todo>,
 }
 
 impl Collection {
     #[cfg(feature = "std")]
-    pub fn open_reader<T: std::io::Read>(
-        read: T,
-    ) -> Result<Collection, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn open_reader<T: std::io::Read>(read: T) -> Result<Collection, ReadError> {
         let buf_reader = std::io::BufReader::new(read);
         let lines = buf_reader.lines();
 
         let mut todos = Vec::default();
 
-        for line in lines {
-            let line = line?;
-            let todo = parse(&line)?;
-            todos.push(Todo::from(todo)?);
+        for (idx, line) in lines.enumerate() {
+            let line = line.map_err(ReadError::Io)?;
+            let todo = parse(&line)
+                .and_then(Todo::from)
+                .map_err(|error| ReadError::Parse {
+                    line: idx + 1,
+                    error,
+                })?;
+            todos.push(todo);
         }
 
         Ok(Collection { todos })
     }
 
     #[cfg(feature = "std")]
-    pub fn write_writer<W: std::io::Write>(
-        &self,
-        writer: &mut W,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub fn write_writer<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
         for todo in &self.todos {
-            write!(writer, "{}\n", todo)?;
+            writeln!(writer, "{todo}")?;
         }
         Ok(())
     }
 
+    pub fn get(&self, idx: usize) -> Option<&Todo> {
+        self.todos.get(idx)
+    }
+
     pub fn get_mut(&mut self, idx: usize) -> Option<&mut Todo> {
         self.todos.get_mut(idx)
     }
@@ -169,8 +253,7 @@ impl Collection {
     pub fn projects(&self) -> BTreeSet<&str> {
         self.todos
             .iter()
-            .map(|m| m.projects.iter())
-            .flatten()
+            .flat_map(|m| m.projects.iter())
             .map(|m| m.as_str())
             .collect()
     }
@@ -178,8 +261,7 @@ impl Collection {
     pub fn contexts(&self) -> BTreeSet<&str> {
         self.todos
             .iter()
-            .map(|m| m.contexts.iter())
-            .flatten()
+            .flat_map(|m| m.contexts.iter())
             .map(|m| m.as_str())
             .collect()
     }
@@ -192,6 +274,14 @@ impl Collection {
         self.todos.iter()
     }
 
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Todo> {
+        self.todos.iter_mut()
+    }
+
+    pub fn overdue<'a>(&'a self, clock: &'a impl Clock) -> impl Iterator<Item = &'a Todo> + 'a {
+        self.todos.iter().filter(|m| m.is_overdue(clock))
+    }
+
     pub fn len(&self) -> usize {
         self.todos.len()
     }
@@ -200,3 +290,115 @@ impl Collection {
         self.todos.is_empty()
     }
 }
+
+impl fmt::Display for Collection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for todo in &self.todos {
+            writeln!(f, "{todo}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<Todo> for Collection {
+    fn from_iter<T: IntoIterator<Item = Todo>>(iter: T) -> Self {
+        Collection {
+            todos: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Todo> for Collection {
+    fn extend<T: IntoIterator<Item = Todo>>(&mut self, iter: T) {
+        self.todos.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedClock;
+
+    const TODAY: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+    fn todo(line: &str) -> Todo {
+        Todo::from(parse(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn due_is_the_first_date() {
+        assert_eq!(todo("Pay rent").due(), None);
+        assert_eq!(todo("Pay rent due:soon").due(), None);
+        assert_eq!(todo("Pay rent due:2026-10-19").due(), Some(TODAY));
+    }
+
+    #[test]
+    fn set_due_keeps_key_order() {
+        let mut todo = todo("Pay rent due:2026-10-01 rec:\"1m\"");
+        todo.set_due(Some(TODAY));
+        assert_eq!(todo.to_string(), "Pay rent due:2026-10-19 rec:\"1m\"");
+
+        todo.set_due(None);
+        assert_eq!(todo.due(), None);
+        assert_eq!(todo.to_string(), "Pay rent rec:\"1m\"");
+
+        todo.set_due(Some(TODAY));
+        assert_eq!(todo.to_string(), "Pay rent rec:\"1m\" due:2026-10-19");
+    }
+
+    #[test]
+    fn overdue_starts_the_day_after_due() {
+        let clock = FixedClock(TODAY);
+        assert!(todo("Pay rent due:2026-10-18").is_overdue(&clock));
+        assert!(!todo("Pay rent due:2026-10-19").is_overdue(&clock));
+        assert!(!todo("Pay rent due:2026-10-20").is_overdue(&clock));
+        assert!(!todo("Pay rent").is_overdue(&clock));
+        assert!(!todo("x Pay rent due:2026-10-18").is_overdue(&clock));
+    }
+
+    #[test]
+    fn complete_and_reopen() {
+        let clock = FixedClock(TODAY);
+        let mut todo = todo("(A) 2026-10-01 Pay rent due:2026-10-18");
+
+        todo.complete(&clock);
+        assert!(todo.done);
+        assert_eq!(todo.completed, Some(TODAY));
+        assert!(!todo.is_overdue(&clock));
+
+        todo.reopen();
+        assert!(!todo.done);
+        assert_eq!(todo.completed, None);
+        assert!(todo.is_overdue(&clock));
+    }
+
+    #[test]
+    fn complete_leaves_recurrence_alone() {
+        let mut todo = todo("Pay rent due:2026-10-01 rec:\"1m\"");
+        todo.complete(&FixedClock(TODAY));
+        assert_eq!(todo.due(), NaiveDate::from_ymd_opt(2026, 10, 1));
+        assert_eq!(todo.get("rec"), Some(&Value::String("1m".into())));
+
+        todo.reopen();
+        assert_eq!(todo.to_string(), "Pay rent due:2026-10-01 rec:\"1m\"");
+    }
+
+    #[test]
+    fn overdue_filters_the_collection() {
+        let todos = [
+            "Pay rent due:2026-10-18",
+            "Call mom due:2026-10-19",
+            "Water plants",
+            "x Renew passport due:2026-09-01",
+        ]
+        .into_iter()
+        .map(todo)
+        .collect::<Collection>();
+
+        let overdue = todos
+            .overdue(&FixedClock(TODAY))
+            .map(|m| m.description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(overdue, ["Pay rent"]);
+    }
+}
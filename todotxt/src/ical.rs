@@ -0,0 +1,441 @@
+//! Conversion between a [`Collection`] and iCalendar (RFC 5545) `VTODO` components.
+//!
+//! Todos map onto the following properties:
+//!
+//! | todo.txt          | iCalendar             |
+//! |-------------------|-----------------------|
+//! | description       | `SUMMARY`             |
+//! | priority `(A)`    | `PRIORITY:1` .. `9`   |
+//! | `due:` key        | `DUE;VALUE=DATE`      |
+//! | completion        | `STATUS`, `COMPLETED` |
+//! | creation date     | `CREATED`             |
+//! | `@contexts`       | `CATEGORIES`          |
+//! | `+projects`       | `X-TODOTXT-PROJECTS`  |
+//! | `uid:` key        | `UID`                 |
+use core::fmt::{self, Write};
+
+use chrono::NaiveDate;
+
+use crate::{
+    Clock, Collection, Todo, Value,
+    parser::{to_description, to_name},
+};
+
+pub const UID_KEY: &str = "uid";
+
+const PRODID: &str = "-//todotxt//todotxt//EN";
+const PROJECTS_PROPERTY: &str = "X-TODOTXT-PROJECTS";
+const MAX_LINE_LENGTH: usize = 75;
+
+#[derive(Debug)]
+pub struct Error {
+    line: usize,
+    message: String,
+}
+
+impl Error {
+    fn new(line: usize, message: impl Into<String>) -> Error {
+        Error {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Render `collection` as a `VCALENDAR` document.
+/// The clock provides the mandatory `DTSTAMP` property.
+pub fn to_ics(collection: &Collection, clock: &impl Clock) -> String {
+    let mut out = String::new();
+    write_ics(collection, clock, &mut out).expect("writing to a string");
+    out
+}
+
+pub fn write_ics<W: Write>(
+    collection: &Collection,
+    clock: &impl Clock,
+    out: &mut W,
+) -> fmt::Result {
+    let stamp = clock.today();
+
+    write_line(out, "BEGIN:VCALENDAR")?;
+    write_line(out, "VERSION:2.0")?;
+    write_line(out, &format!("PRODID:{PRODID}"))?;
+
+    for todo in collection.iter() {
+        write_todo(out, todo, stamp)?;
+    }
+
+    write_line(out, "END:VCALENDAR")
+}
+
+fn write_todo<W: Write>(out: &mut W, todo: &Todo, stamp: NaiveDate) -> fmt::Result {
+    write_line(out, "BEGIN:VTODO")?;
+    write_line(out, &format!("UID:{}", escape(&uid(todo))))?;
+    write_line(out, &format!("DTSTAMP:{}", date_time(stamp)))?;
+    write_line(out, &format!("SUMMARY:{}", escape(&todo.description)))?;
+
+    if let Some(created) = todo.created {
+        write_line(out, &format!("CREATED:{}", date_time(created)))?;
+    }
+
+    if let Some(priority) = todo.priority {
+        write_line(out, &format!("PRIORITY:{}", priority_to_ics(priority)))?;
+    }
+
+    if let Some(due) = todo.due() {
+        write_line(out, &format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")))?;
+    }
+
+    if todo.done {
+        write_line(out, "STATUS:COMPLETED")?;
+        if let Some(completed) = todo.completed {
+            write_line(out, &format!("COMPLETED:{}", date_time(completed)))?;
+        }
+    } else {
+        write_line(out, "STATUS:NEEDS-ACTION")?;
+    }
+
+    if !todo.contexts.is_empty() {
+        write_line(out, &format!("CATEGORIES:{}", join(&todo.contexts)))?;
+    }
+
+    if !todo.projects.is_empty() {
+        write_line(
+            out,
+            &format!("{PROJECTS_PROPERTY}:{}", join(&todo.projects)),
+        )?;
+    }
+
+    write_line(out, "END:VTODO")
+}
+
+/// Parse every `VTODO` component in `input`. Other components are ignored.
+///
+/// Summaries are [escaped](to_description) so they don't turn into todo.txt syntax.
+pub fn from_ics(input: &str) -> Result<Collection, Error> {
+    let mut todos = Vec::default();
+    let mut current: Option<Todo> = None;
+
+    for (line_no, line) in unfold(input) {
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Error::new(line_no, "expected property value"));
+        };
+
+        let name = name
+            .split_once(';')
+            .map_or(name, |(name, _)| name)
+            .to_ascii_uppercase();
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VTODO") => {
+                current = Some(Todo::default());
+            }
+            ("BEGIN", Some(_)) if value.eq_ignore_ascii_case("VTODO") => {
+                return Err(Error::new(line_no, "nested VTODO"));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VTODO") => {
+                let todo = current.take().unwrap();
+                if todo.description.is_empty() {
+                    return Err(Error::new(line_no, "VTODO without SUMMARY"));
+                }
+                todos.push(todo);
+            }
+            (_, None) => {}
+            ("UID", Some(todo)) => {
+                todo.values
+                    .insert(UID_KEY.to_string(), vec![Value::String(unescape(value))]);
+            }
+            ("SUMMARY", Some(todo)) => {
+                todo.description = to_description(&unescape(value));
+            }
+            ("DESCRIPTION", Some(todo)) if todo.description.is_empty() => {
+                todo.description = to_description(&unescape(value));
+            }
+            ("PRIORITY", Some(todo)) => {
+                let priority = value
+                    .trim()
+                    .parse::<u8>()
+                    .map_err(|_| Error::new(line_no, "invalid PRIORITY"))?;
+                todo.priority = priority_from_ics(priority);
+            }
+            ("DUE", Some(todo)) => {
+                todo.set_due(Some(parse_date(line_no, value)?));
+            }
+            ("CREATED", Some(todo)) => {
+                todo.created = Some(parse_date(line_no, value)?);
+            }
+            ("COMPLETED", Some(todo)) => {
+                todo.completed = Some(parse_date(line_no, value)?);
+                todo.done = true;
+            }
+            ("STATUS", Some(todo)) => {
+                todo.done = value.eq_ignore_ascii_case("COMPLETED");
+                if !todo.done {
+                    todo.completed = None;
+                }
+            }
+            ("CATEGORIES", Some(todo)) => {
+                todo.contexts.extend(split(value));
+            }
+            (PROJECTS_PROPERTY, Some(todo)) => {
+                todo.projects.extend(split(value));
+            }
+            _ => {}
+        }
+    }
+
+    if current.is_some() {
+        return Err(Error::new(input.lines().count(), "unterminated VTODO"));
+    }
+
+    Ok(Collection::from_iter(todos))
+}
+
+fn uid(todo: &Todo) -> String {
+    if let Some(Value::String(uid)) = todo.get(UID_KEY) {
+        return uid.clone();
+    }
+
+    // FNV-1a, so the same todo keeps its UID across versions and machines
+    let created = todo.created.map(|m| m.to_string()).unwrap_or_default();
+    let identity = [
+        todo.description.as_str(),
+        &created,
+        &todo.projects.join(" "),
+    ]
+    .join("\n");
+    let hash = identity.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}@todotxt")
+}
+
+fn priority_to_ics(priority: char) -> u8 {
+    match priority.to_ascii_uppercase() {
+        p @ 'A'..='I' => p as u8 - b'A' + 1,
+        _ => 9,
+    }
+}
+
+fn priority_from_ics(priority: u8) -> Option<char> {
+    match priority {
+        1..=9 => Some((b'A' + priority - 1) as char),
+        _ => None,
+    }
+}
+
+fn date_time(date: NaiveDate) -> String {
+    format!("{}T000000Z", date.format("%Y%m%d"))
+}
+
+fn parse_date(line: usize, value: &str) -> Result<NaiveDate, Error> {
+    value
+        .get(..8)
+        .and_then(|m| NaiveDate::parse_from_str(m, "%Y%m%d").ok())
+        .ok_or_else(|| Error::new(line, format!("invalid date: {value}")))
+}
+
+fn join(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|m| escape(m))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn split(value: &str) -> impl Iterator<Item = String> + '_ {
+    let mut items = Vec::default();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push('\\');
+                    current.push(next);
+                }
+            }
+            ',' => items.push(core::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    items.push(current);
+
+    // Categories can contain anything, names can't
+    items.into_iter().filter_map(|m| {
+        let item = unescape(m.trim())
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("_");
+        to_name(&item)
+    })
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Write a content line, folding it at 75 octets as required by the spec.
+fn write_line<W: Write>(out: &mut W, line: &str) -> fmt::Result {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_LENGTH {
+            out.write_str("\r\n ")?;
+            width = 1;
+        }
+        out.write_char(c)?;
+        width += c.len_utf8();
+    }
+    out.write_str("\r\n")
+}
+
+/// Join folded lines, yielding each logical line with its (1-based) starting line number.
+fn unfold(input: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::default();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some((_, last)) = lines.last_mut()
+        {
+            last.push_str(rest);
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        lines.push((idx + 1, line.to_string()));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const ICS: &str = "\
+BEGIN:VCALENDAR\r
+BEGIN:VTODO\r
+SUMMARY:Plan budget\r
+CATEGORIES:R&D,Work/Home,Home Office,&&\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn categories_become_valid_contexts() {
+        let todos = from_ics(ICS).unwrap();
+        let todo = todos.get(0).unwrap();
+        assert_eq!(todo.contexts, ["R-D", "Work-Home", "Home_Office"]);
+
+        let line = todos.to_string();
+        let parsed = Todo::from(parse(line.trim()).unwrap()).unwrap();
+        assert_eq!(&parsed, todo);
+    }
+
+    #[test]
+    fn summaries_are_escaped() {
+        let ics = [
+            "Ask @anna about budget",
+            "Agenda: review",
+            "x marks the spot",
+            "2026-01-01 kickoff",
+        ]
+        .iter()
+        .map(|m| format!("BEGIN:VTODO\r\nSUMMARY:{m}\r\nEND:VTODO\r\n"))
+        .collect::<String>();
+
+        let todos = from_ics(&ics).unwrap();
+        let lines = todos.to_string();
+        assert_eq!(
+            lines.lines().collect::<Vec<_>>(),
+            [
+                "Ask ＠anna about budget",
+                "Agenda： review",
+                "ｘ marks the spot",
+                "2026－01-01 kickoff",
+            ]
+        );
+        for (line, todo) in lines.lines().zip(todos.iter()) {
+            assert_eq!(&Todo::from(parse(line).unwrap()).unwrap(), todo);
+        }
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let clock = crate::FixedClock(NaiveDate::from_ymd_opt(2026, 10, 19).unwrap());
+        let todos = [
+            "(A) 2026-10-01 Plan budget +office @work due:2026-11-01",
+            "X 2026-10-18 2026-10-02 Ship it +site uid:abc-123",
+            "Call the office @büro",
+        ]
+        .into_iter()
+        .map(|m| Todo::from(parse(m).unwrap()).unwrap())
+        .collect::<Collection>();
+
+        let ics = to_ics(&todos, &clock);
+        let imported = from_ics(&ics).unwrap();
+        assert_eq!(to_ics(&imported, &clock), ics);
+
+        let lines = imported.to_string();
+        let reparsed = lines
+            .lines()
+            .map(|m| Todo::from(parse(m).unwrap()).unwrap())
+            .collect::<Collection>();
+        assert_eq!(to_ics(&reparsed, &clock), ics);
+    }
+
+    #[test]
+    fn uid_is_stable() {
+        let todo = Todo::from(parse("2026-10-01 Fix header +site").unwrap()).unwrap();
+        assert_eq!(uid(&todo), "3e04440dfb6d6cfe@todotxt");
+
+        let mut done = todo.clone();
+        done.done = true;
+        assert_eq!(uid(&done), uid(&todo));
+    }
+}
@@ -1,4 +1,7 @@
+mod clock;
 mod collection;
+pub mod ical;
 pub mod parser;
+pub mod quick;
 
-pub use self::collection::*;
+pub use self::{clock::*, collection::*};
@@ -3,7 +3,7 @@ use udled::{
     Input, Lex, Tokenizer, WithSpan, any,
     token::{Char, Digit, EOF, Many, Opt, Spanned, Test},
 };
-use udled_tokenizers::{Bool, Float, Ident, Int, Str};
+use udled_tokenizers::{Bool, Float, Int, Str};
 
 pub fn parse<'a>(input: &'a str) -> Result<Todo<'a>, udled::Error> {
     let mut input = Input::new(input);
@@ -97,7 +97,7 @@ impl Tokenizer for DescriptionTokenizer {
 
             if reader.peek(Test((
                 Many(' '),
-                any!(('@', Ident), ('+', Ident), (Ident, ':')),
+                any!(('@', Name), ('+', Name), (Key, ':')),
             )))? {
                 break;
             }
@@ -105,10 +105,6 @@ impl Tokenizer for DescriptionTokenizer {
             end = reader.parse(Char)?.span();
         }
 
-        if start == end {
-            return Err(reader.error("Expected description"));
-        }
-
         let span = start + end;
 
         Ok(Lex::new(span.slice(reader.source()).unwrap(), span))
@@ -166,7 +162,8 @@ impl Tokenizer for CompletionParser {
         &self,
         reader: &mut udled::Reader<'_, 'a>,
     ) -> Result<Self::Token<'a>, udled::Error> {
-        reader.parse(any!("x", "X")).map(|_| true)
+        // Only followed by a space, so `xylophone` is an open todo
+        reader.parse(any!(("x", ' '), ("X", ' '))).map(|_| true)
     }
 }
 
@@ -180,18 +177,225 @@ impl Tokenizer for ItemTokenizer {
         reader: &mut udled::Reader<'_, 'a>,
     ) -> Result<Self::Token<'a>, udled::Error> {
         if reader.peek('+')? {
-            let (_, ident) = reader.parse(('+', Ident))?;
+            let (_, ident) = reader.parse(('+', Name))?;
             Ok(Item::Tag(ident))
         } else if reader.peek('@')? {
-            let (_, ident) = reader.parse(('@', Ident))?;
+            let (_, ident) = reader.parse(('@', Name))?;
             Ok(Item::Context(ident))
         } else {
-            let (key, _, value) = reader.parse((Ident, (SPACE, ':', SPACE), ValueParser))?;
+            let (key, _, value) = reader.parse((Key, (SPACE, ':', SPACE), ValueParser))?;
             Ok(Item::KeyVal { key, value })
         }
     }
 }
 
+/// Identifier rules for `+project` and `@context` names, `key:` keys and unquoted values.
+///
+/// Names consist of Unicode `XID_Continue` characters and emoji, optionally
+/// joined by single `-` or `.` separators; a name may start with a digit
+/// (`+2024-planning`), but never starts or ends with a separator, so a
+/// `+website.` ending a sentence is no name and stays in the description. Keys and
+/// unquoted values follow the stricter `XID_Start XID_Continue*` rule (with
+/// `-` as separator), so times like `15:30` in a description are never
+/// mistaken for a key.
+pub fn is_name(input: &str) -> bool {
+    is_identifier(input, NAME)
+}
+
+/// Turn arbitrary text into a name by replacing runs of other characters
+/// with `-`, so `R&D` becomes `R-D`. Returns `None` if nothing is left.
+pub fn to_name(input: &str) -> Option<String> {
+    let mut name = String::new();
+    let mut separate = false;
+
+    for c in input.chars() {
+        let valid = if name.is_empty() {
+            (NAME.first)(c)
+        } else {
+            (NAME.rest)(c)
+        };
+
+        if valid {
+            if separate {
+                name.push('-');
+                separate = false;
+            }
+            name.push(c);
+        } else if !name.is_empty() {
+            separate = true;
+        }
+    }
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Turn arbitrary text into a description that parses back as written.
+///
+/// Whitespace is collapsed into single spaces, and characters that would start
+/// todo.txt syntax, like the `@` of `@anna`, the `:` of `http:` or a leading
+/// completion mark, priority or date, are replaced by their fullwidth forms.
+pub fn to_description(input: &str) -> String {
+    let chars = input.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = chars.chars().collect::<Vec<_>>();
+
+    for idx in 0..chars.len() {
+        let escape = match chars[idx] {
+            // The first character always belongs to the description
+            '@' | '+' => idx > 0 && chars.get(idx + 1).copied().is_some_and(NAME.first),
+            ':' => (1..idx).any(|start| is_key(&chars[start..idx].iter().collect::<String>())),
+            _ => false,
+        };
+        if escape {
+            chars[idx] = fullwidth(chars[idx]);
+        }
+    }
+
+    let marker = match chars.as_slice() {
+        ['x' | 'X'] | ['x' | 'X', ' ', ..] => Some(0),
+        ['(', _, ')', ..] => Some(0),
+        [a, b, c, d, '-', e, f, '-', g, h, ..]
+            if [a, b, c, d, e, f, g, h].iter().all(|m| m.is_ascii_digit()) =>
+        {
+            Some(4)
+        }
+        _ => None,
+    };
+    if let Some(idx) = marker {
+        chars[idx] = fullwidth(chars[idx]);
+    }
+
+    chars.into_iter().collect()
+}
+
+fn fullwidth(c: char) -> char {
+    char::from_u32(c as u32 - 0x21 + 0xFF01).unwrap_or(c)
+}
+
+pub fn is_key(input: &str) -> bool {
+    is_identifier(input, KEY)
+}
+
+#[derive(Clone, Copy)]
+struct Rules {
+    first: fn(char) -> bool,
+    rest: fn(char) -> bool,
+    separator: fn(char) -> bool,
+}
+
+const NAME: Rules = Rules {
+    first: |c| (unicode_ident::is_xid_continue(c) && c != '_') || is_emoji(c),
+    rest: |c| unicode_ident::is_xid_continue(c) || is_emoji(c) || is_emoji_modifier(c),
+    separator: |c| matches!(c, '-' | '.'),
+};
+
+const KEY: Rules = Rules {
+    first: |c| unicode_ident::is_xid_start(c) || c == '_',
+    rest: unicode_ident::is_xid_continue,
+    separator: |c| c == '-',
+};
+
+fn is_identifier(input: &str, rules: Rules) -> bool {
+    let mut chars = input.chars();
+    if !chars.next().is_some_and(rules.first) {
+        return false;
+    }
+
+    let mut after_separator = false;
+    for c in chars {
+        if (rules.rest)(c) {
+            after_separator = false;
+        } else if (rules.separator)(c) && !after_separator {
+            after_separator = true;
+        } else {
+            return false;
+        }
+    }
+
+    !after_separator
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x1F000..=0x1FAFF
+    )
+}
+
+/// Characters that only appear inside emoji sequences:
+/// zero width joiner, variation selector 16 and the keycap combiner.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c, '\u{200D}' | '\u{FE0F}' | '\u{20E3}')
+}
+
+struct Name;
+
+impl Tokenizer for Name {
+    type Token<'a> = Lex<'a>;
+
+    fn to_token<'a>(
+        &self,
+        reader: &mut udled::Reader<'_, 'a>,
+    ) -> Result<Self::Token<'a>, udled::Error> {
+        identifier(reader, NAME)
+    }
+}
+
+struct Key;
+
+impl Tokenizer for Key {
+    type Token<'a> = Lex<'a>;
+
+    fn to_token<'a>(
+        &self,
+        reader: &mut udled::Reader<'_, 'a>,
+    ) -> Result<Self::Token<'a>, udled::Error> {
+        identifier(reader, KEY)
+    }
+}
+
+fn identifier<'a>(
+    reader: &mut udled::Reader<'_, 'a>,
+    rules: Rules,
+) -> Result<Lex<'a>, udled::Error> {
+    let start = reader.parse(CharIf(rules.first))?.span();
+    let mut end = start;
+
+    loop {
+        if reader.peek(CharIf(rules.rest))? {
+            end = reader.parse(CharIf(rules.rest))?.span();
+        } else if reader.peek((CharIf(rules.separator), CharIf(rules.rest)))? {
+            // Separators only count when another identifier character follows
+            let (_, lex) = reader.parse((CharIf(rules.separator), CharIf(rules.rest)))?;
+            end = lex.span();
+        } else {
+            break;
+        }
+    }
+
+    let span = start + end;
+
+    Ok(Lex::new(span.slice(reader.source()).unwrap(), span))
+}
+
+struct CharIf(fn(char) -> bool);
+
+impl Tokenizer for CharIf {
+    type Token<'a> = Lex<'a>;
+
+    fn to_token<'a>(
+        &self,
+        reader: &mut udled::Reader<'_, 'a>,
+    ) -> Result<Self::Token<'a>, udled::Error> {
+        let lex = reader.parse(Char)?;
+
+        if !lex.as_str().chars().next().is_some_and(self.0) {
+            return Err(reader.error("Expected identifier"));
+        }
+
+        Ok(lex)
+    }
+}
+
 struct ValueParser;
 
 impl Tokenizer for ValueParser {
@@ -209,8 +413,8 @@ impl Tokenizer for ValueParser {
             Ok(Value::Int(reader.parse(Int)?))
         } else if reader.peek(Bool)? {
             Ok(Value::Bool(reader.parse(Bool)?))
-        } else if reader.peek(Ident)? {
-            Ok(Value::String(reader.parse(Ident)?))
+        } else if reader.peek(Key)? {
+            Ok(Value::String(reader.parse(Key)?))
         } else {
             Ok(Value::String(reader.parse(Str)?))
         }
@@ -242,3 +446,82 @@ pub struct Todo<'a> {
     pub created: Option<udled::Item<NaiveDate>>,
     pub completed: Option<udled::Item<NaiveDate>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    fn round_trip(line: &str) -> crate::Todo {
+        let todo = crate::Todo::from(parse(line).unwrap()).unwrap();
+        assert_eq!(todo.to_string(), line);
+        todo
+    }
+
+    #[test]
+    fn unicode_names_round_trip() {
+        assert_eq!(round_trip("Clean up +køkken").projects, ["køkken"]);
+        assert_eq!(
+            round_trip("Draft goals +2024-planning").projects,
+            ["2024-planning"]
+        );
+        assert_eq!(round_trip("Call the office @büro").contexts, ["büro"]);
+        assert_eq!(round_trip("Water plants @🏠").contexts, ["🏠"]);
+        assert_eq!(round_trip("Book flight @🇩🇰").contexts, ["🇩🇰"]);
+
+        let todo = round_trip("Review budget owner:søren");
+        assert_eq!(todo.get("owner"), Some(&Value::String("søren".into())));
+    }
+
+    #[test]
+    fn descriptions_round_trip() {
+        let created = chrono::NaiveDate::from_ymd_opt(2026, 10, 19);
+
+        for text in [
+            "Ask @anna about budget",
+            "Agenda: review",
+            "see http://example.com for details",
+            "mail anna@example.com",
+            "x marks the spot",
+            "X",
+            "(A) first",
+            "2026-01-01 kickoff",
+            "1+1",
+            "a",
+            "tabs\tand\nnewlines  ",
+        ] {
+            let description = to_description(text);
+            for created in [None, created] {
+                let todo = crate::Todo {
+                    description: description.clone(),
+                    created,
+                    projects: vec!["site".into()],
+                    ..Default::default()
+                };
+                let line = todo.to_string();
+                assert_eq!(
+                    crate::Todo::from(parse(&line).unwrap()).unwrap(),
+                    todo,
+                    "{line}"
+                );
+            }
+        }
+
+        assert_eq!(
+            to_description("C++ at 15:30 for @ most"),
+            "C++ at 15:30 for @ most"
+        );
+        assert_eq!(to_description("xylophone"), "xylophone");
+    }
+
+    #[test]
+    fn trailing_punctuation_stays_in_the_description() {
+        let todo = round_trip("Update +website.");
+        assert_eq!(todo.description, "Update +website.");
+        assert!(todo.projects.is_empty());
+
+        let todo = round_trip("Ship it. +website @work");
+        assert_eq!(todo.description, "Ship it.");
+        assert_eq!(todo.projects, ["website"]);
+    }
+}
@@ -0,0 +1,461 @@
+//! Natural-language front end for [`parse`](crate::parser::parse).
+//!
+//! Phrases are only recognized at the end of the input, after the todo
+//! itself, where they are removed and turned into todo fields. Everything
+//! else is parsed as a regular todo.txt line, and fields given the todo.txt
+//! way win over phrases:
+//!
+//! - priority: `(A)`, or `p:A` anywhere after the description
+//! - due dates: `today`, `tomorrow`, `monday`, `next friday`, `next week`,
+//!   `in 3 days`, `the 1st`, `2024-05-01`, optionally prefixed by `on`, `by` or `due`
+//! - times: `3pm`, `3:30pm`, `15:30`, `noon`, optionally prefixed by `at`
+//! - recurrence: `daily`, `weekly`, `every month`, `every 2 weeks`,
+//!   `every monday`, `every month on the 1st`
+//!
+//! For example `pay rent +home every month on the 1st (A)` becomes
+//! `(A) pay rent +home due:<next 1st> rec:"1m"`, while `write weekly report`
+//! is left alone.
+use chrono::{Datelike, Months, NaiveDate, NaiveTime, TimeDelta, Weekday};
+
+use crate::{Clock, Todo, Value, parser};
+
+pub const TIME_KEY: &str = "time";
+pub const RECURRENCE_KEY: &str = "rec";
+pub const PRIORITY_KEY: &str = "p";
+
+pub fn parse(input: &str, clock: &impl Clock) -> Result<Todo, udled::Error> {
+    let today = clock.today();
+    let words = input.split_whitespace().collect::<Vec<_>>();
+
+    // Keep the completion mark, priority, dates and at least one word of the todo
+    let first = prefix(&words) + 1;
+    let (mut todo, mut fields) =
+        match (first..words.len()).find_map(|idx| Some((idx, phrases(&words[idx..], today)?))) {
+            Some((end, (fields, items))) => {
+                let mut line = words[..end].to_vec();
+                line.extend(items);
+                (Todo::from(parser::parse(&line.join(" "))?)?, fields)
+            }
+            None => (Todo::from(parser::parse(input)?)?, Fields::default()),
+        };
+
+    // `p:A` is shorthand for a priority
+    if let Some(priority) = todo.get(PRIORITY_KEY).and_then(shorthand_priority) {
+        todo.values.shift_remove(PRIORITY_KEY);
+        fields.priority = fields.priority.or(Some(priority));
+    }
+
+    if todo.priority.is_none() {
+        todo.priority = fields.priority;
+    }
+
+    if fields.time.is_some() && fields.due.is_none() {
+        fields.due = Some(today);
+    }
+
+    if let Some(due) = fields.due
+        && todo.due().is_none()
+    {
+        todo.set_due(Some(due));
+    }
+
+    if let Some(time) = fields.time {
+        todo.values
+            .entry(TIME_KEY.to_string())
+            .or_insert_with(|| vec![Value::String(time.format("%H:%M").to_string())]);
+    }
+
+    if let Some(recurrence) = fields.recurrence {
+        todo.values
+            .entry(RECURRENCE_KEY.to_string())
+            .or_insert_with(|| vec![Value::String(recurrence)]);
+    }
+
+    Ok(todo)
+}
+
+/// Number of words making up the standard todo.txt prefix:
+/// `x`, a priority and up to two dates.
+fn prefix(words: &[&str]) -> usize {
+    let mut idx = usize::from(words.first().is_some_and(|m| *m == "x" || *m == "X"));
+    if words.get(idx).is_some_and(|m| priority(m).is_some()) {
+        idx += 1;
+    }
+    while idx < words.len().min(3) && NaiveDate::parse_from_str(words[idx], "%Y-%m-%d").is_ok() {
+        idx += 1;
+    }
+    idx
+}
+
+/// Match `words` entirely as phrases, possibly mixed with `+projects`,
+/// `@contexts` and `key:value` pairs, which are kept. `None` unless there
+/// is at least one phrase.
+fn phrases<'a>(words: &[&'a str], today: NaiveDate) -> Option<(Fields, Vec<&'a str>)> {
+    let mut fields = Fields::default();
+    let mut items = Vec::default();
+    let mut found = false;
+
+    let mut idx = 0;
+    while idx < words.len() {
+        if let Some((consumed, phrase)) = match_phrase(&words[idx..], today) {
+            fields.apply(phrase);
+            found = true;
+            idx += consumed;
+            continue;
+        }
+
+        let word = words[idx];
+        if !(word.starts_with('+') || word.starts_with('@') || word.contains(':')) {
+            return None;
+        }
+        items.push(word);
+        idx += 1;
+    }
+
+    found.then_some((fields, items))
+}
+
+#[derive(Default)]
+struct Fields {
+    priority: Option<char>,
+    due: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    recurrence: Option<String>,
+}
+
+impl Fields {
+    fn apply(&mut self, phrase: Phrase) {
+        match phrase {
+            Phrase::Priority(priority) => self.priority = Some(priority),
+            Phrase::Due(due) => self.due = Some(due),
+            Phrase::Time(time) => self.time = Some(time),
+            Phrase::Recurrence { interval, first } => {
+                self.recurrence = Some(interval);
+                if self.due.is_none() {
+                    self.due = Some(first);
+                }
+            }
+        }
+    }
+}
+
+enum Phrase {
+    Priority(char),
+    Due(NaiveDate),
+    Time(NaiveTime),
+    Recurrence { interval: String, first: NaiveDate },
+}
+
+fn match_phrase(words: &[&str], today: NaiveDate) -> Option<(usize, Phrase)> {
+    if let Some(priority) = priority(words[0]) {
+        return Some((1, Phrase::Priority(priority)));
+    }
+
+    if let Some((consumed, interval, first)) = recurrence(words, today) {
+        return Some((consumed, Phrase::Recurrence { interval, first }));
+    }
+
+    if let Some((consumed, time)) = time(words) {
+        return Some((consumed, Phrase::Time(time)));
+    }
+
+    let skip = match lower(words[0]).as_str() {
+        "on" | "by" | "due" => 1,
+        _ => 0,
+    };
+
+    date(&words[skip..], today).map(|(consumed, due)| (skip + consumed, Phrase::Due(due)))
+}
+
+fn priority(word: &str) -> Option<char> {
+    let inner = word.strip_prefix('(')?.strip_suffix(')')?;
+
+    let mut chars = inner.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() => Some(c.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+fn shorthand_priority(value: &Value) -> Option<char> {
+    let Value::String(value) = value else {
+        return None;
+    };
+
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => Some(c),
+        _ => None,
+    }
+}
+
+fn recurrence(words: &[&str], today: NaiveDate) -> Option<(usize, String, NaiveDate)> {
+    let (mut consumed, interval, mut first) = match lower(words[0]).as_str() {
+        "daily" => (1, "1d".to_string(), today),
+        "weekly" => (1, "1w".to_string(), today),
+        "monthly" => (1, "1m".to_string(), today),
+        "yearly" | "annually" => (1, "1y".to_string(), today),
+        "every" => {
+            let next = lower(words.get(1)?);
+            if let Some(weekday) = weekday(&next) {
+                (2, "1w".to_string(), upcoming(today, weekday, false))
+            } else if let Some(unit) = unit(&next) {
+                (2, format!("1{unit}"), today)
+            } else {
+                let count = match next.as_str() {
+                    "other" => 2,
+                    n => n.parse::<u32>().ok().filter(|n| *n > 0)?,
+                };
+                let unit = unit(&lower(words.get(2)?))?;
+                (3, format!("{count}{unit}"), today)
+            }
+        }
+        _ => return None,
+    };
+
+    if words.get(consumed).is_some_and(|m| lower(m) == "on")
+        && let Some((anchor_consumed, anchor)) = date(&words[consumed + 1..], today)
+    {
+        consumed += anchor_consumed + 1;
+        first = anchor;
+    }
+
+    Some((consumed, interval, first))
+}
+
+fn date(words: &[&str], today: NaiveDate) -> Option<(usize, NaiveDate)> {
+    let word = lower(words.first()?);
+
+    match word.as_str() {
+        "today" | "tonight" => return Some((1, today)),
+        "tomorrow" | "tmrw" => return Some((1, today + TimeDelta::days(1))),
+        "next" => {
+            let next = lower(words.get(1)?);
+            let date = match next.as_str() {
+                "week" => today + TimeDelta::days(7),
+                "month" => today.checked_add_months(Months::new(1))?,
+                "year" => today.checked_add_months(Months::new(12))?,
+                _ => upcoming(today, weekday(&next)?, true),
+            };
+            return Some((2, date));
+        }
+        "in" => {
+            let count = words.get(1)?.parse::<u32>().ok()?;
+            let date = match unit(&lower(words.get(2)?))? {
+                'd' => today + TimeDelta::days(count.into()),
+                'w' => today + TimeDelta::weeks(count.into()),
+                'm' => today.checked_add_months(Months::new(count))?,
+                _ => today.checked_add_months(Months::new(count * 12))?,
+            };
+            return Some((3, date));
+        }
+        "the" => {
+            let day = ordinal(&lower(words.get(1)?))?;
+            return Some((2, day_of_month(today, day)?));
+        }
+        _ => {}
+    }
+
+    if let Some(weekday) = weekday(&word) {
+        return Some((1, upcoming(today, weekday, false)));
+    }
+
+    NaiveDate::parse_from_str(&word, "%Y-%m-%d")
+        .ok()
+        .map(|date| (1, date))
+}
+
+fn time(words: &[&str]) -> Option<(usize, NaiveTime)> {
+    let skip = usize::from(lower(words[0]).as_str() == "at");
+    let word = lower(words.get(skip)?);
+
+    match word.as_str() {
+        "noon" => return Some((skip + 1, NaiveTime::from_hms_opt(12, 0, 0)?)),
+        "midnight" => return Some((skip + 1, NaiveTime::from_hms_opt(0, 0, 0)?)),
+        _ => {}
+    }
+
+    // Allow a separate meridiem, as in `3 pm`
+    let (word, consumed) = match words.get(skip + 1).map(|m| lower(m)) {
+        Some(meridiem) if meridiem == "am" || meridiem == "pm" => (word + &meridiem, skip + 2),
+        _ => (word, skip + 1),
+    };
+
+    let (clock, meridiem) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (word.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse::<u32>().ok()?),
+        Some(_) => return None,
+        // A bare number is only a time with a meridiem
+        None if meridiem.is_some() => (clock, 0),
+        None => return None,
+    };
+
+    let mut hour = hour.parse::<u32>().ok()?;
+
+    match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => {
+            hour %= 12;
+            if pm {
+                hour += 12;
+            }
+        }
+        Some(_) => return None,
+        None => {}
+    }
+
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| (consumed, time))
+}
+
+fn unit(word: &str) -> Option<char> {
+    match word.strip_suffix('s').unwrap_or(word) {
+        "day" => Some('d'),
+        "week" => Some('w'),
+        "month" => Some('m'),
+        "year" => Some('y'),
+        _ => None,
+    }
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let weekday = match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+fn ordinal(word: &str) -> Option<u32> {
+    let number = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))?;
+
+    number.parse::<u32>().ok().filter(|m| (1..=31).contains(m))
+}
+
+/// The next date falling on `weekday`. Today counts unless `strict` is set.
+fn upcoming(today: NaiveDate, weekday: Weekday, strict: bool) -> NaiveDate {
+    let mut days =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    if strict && days == 0 {
+        days = 7;
+    }
+    today + TimeDelta::days(days.into())
+}
+
+/// The next date, today included, on the given day of the month.
+/// Months too short for `day` are skipped.
+fn day_of_month(today: NaiveDate, day: u32) -> Option<NaiveDate> {
+    (0..=12).find_map(|offset| {
+        let month = today.checked_add_months(Months::new(offset))?;
+        month.with_day(day).filter(|date| *date >= today)
+    })
+}
+
+fn lower(word: &str) -> String {
+    word.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedClock;
+
+    // A monday
+    const TODAY: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+    fn quick(input: &str) -> Todo {
+        parse(input, &FixedClock(TODAY)).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn words_inside_the_todo_are_kept() {
+        for input in [
+            "write weekly report",
+            "Review the 1st draft",
+            "Meet daily standup team",
+        ] {
+            let todo = quick(input);
+            assert_eq!(todo.to_string(), input);
+            assert!(todo.values.is_empty());
+        }
+    }
+
+    #[test]
+    fn standard_fields_are_not_reinterpreted() {
+        let todo = quick("2026-10-01 Fix header +site");
+        assert_eq!(todo.created, Some(date(10, 1)));
+        assert_eq!(todo.due(), None);
+
+        let todo = quick("x 2026-10-18 2026-10-01 Fix header");
+        assert!(todo.done);
+        assert_eq!(todo.completed, Some(date(10, 18)));
+        assert_eq!(todo.created, Some(date(10, 1)));
+        assert_eq!(todo.due(), None);
+
+        let todo = quick("(B) Fix header due:2026-11-01 p:A tomorrow (A)");
+        assert_eq!(todo.priority, Some('B'));
+        assert_eq!(todo.due(), Some(date(11, 1)));
+        assert_eq!(todo.get(PRIORITY_KEY), None);
+    }
+
+    #[test]
+    fn priority_shorthand() {
+        let todo = quick("pay rent every month on the 1st p:A");
+        assert_eq!(todo.description, "pay rent");
+        assert_eq!(todo.priority, Some('A'));
+        assert_eq!(todo.due(), Some(date(11, 1)));
+        assert_eq!(todo.get(PRIORITY_KEY), None);
+
+        let todo = quick("Fix header p:C");
+        assert_eq!(todo.priority, Some('C'));
+        assert_eq!(todo.get(PRIORITY_KEY), None);
+
+        let todo = quick("Fix header p:low");
+        assert_eq!(todo.priority, None);
+        assert_eq!(todo.get(PRIORITY_KEY), Some(&Value::String("low".into())));
+    }
+
+    #[test]
+    fn trailing_phrases_become_fields() {
+        let todo = quick("pay rent +home every month on the 1st");
+        assert_eq!(todo.description, "pay rent");
+        assert_eq!(todo.projects, ["home"]);
+        assert_eq!(todo.due(), Some(date(11, 1)));
+        assert_eq!(todo.get(RECURRENCE_KEY), Some(&Value::String("1m".into())));
+
+        let todo = quick("call mom tomorrow at 3pm @phone");
+        assert_eq!(todo.description, "call mom");
+        assert_eq!(todo.contexts, ["phone"]);
+        assert_eq!(todo.due(), Some(date(10, 20)));
+        assert_eq!(todo.get(TIME_KEY), Some(&Value::String("15:00".into())));
+
+        let todo = quick("2026-10-01 Ship it next friday (A)");
+        assert_eq!(todo.created, Some(date(10, 1)));
+        assert_eq!(todo.priority, Some('A'));
+        assert_eq!(todo.due(), Some(date(10, 23)));
+    }
+
+    #[test]
+    fn phrases_alone_are_the_todo() {
+        let todo = quick("tomorrow");
+        assert_eq!(todo.description, "tomorrow");
+        assert_eq!(todo.due(), None);
+    }
+}